@@ -0,0 +1,30 @@
+use crate::{Pos, Team};
+
+/// Number of pieces of each team attacking every square of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct AttackMap {
+    white: [[u8; 8]; 8],
+    black: [[u8; 8]; 8],
+}
+
+impl AttackMap {
+    pub(crate) const fn add(&mut self, pos: Pos, team: Team) {
+        match team {
+            Team::White => self.white[pos.rank()][pos.file()] += 1,
+            Team::Black => self.black[pos.rank()][pos.file()] += 1,
+        }
+    }
+
+    #[must_use]
+    pub const fn count(&self, pos: Pos, team: Team) -> u8 {
+        match team {
+            Team::White => self.white[pos.rank()][pos.file()],
+            Team::Black => self.black[pos.rank()][pos.file()],
+        }
+    }
+
+    #[must_use]
+    pub const fn is_attacked(&self, pos: Pos, by: Team) -> bool {
+        self.count(pos, by) > 0
+    }
+}
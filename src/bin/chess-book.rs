@@ -0,0 +1,406 @@
+//! Builds a Polyglot opening book and an opening tree report from PGN files.
+//!
+//! Usage: `chess-book [--out BOOK] [--report FILE] [--plies N] [--min-games N]
+//! [--min-rating N] GAMES.pgn...`
+//!
+//! Games are replayed up to the ply limit or their first castling or promotion,
+//! which the game does not know. Without `--out` and `--report` the report is
+//! printed.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use chess::book::{Book, BookEntry, PolyglotKeys};
+use chess::pgn::{self, Game, GameResult};
+use chess::{Move, Position, Team};
+
+const USAGE: &str = "Usage: chess-book [--out BOOK] [--report FILE] [--plies N] [--min-games N] [--min-rating N] GAMES.pgn...";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    out: Option<PathBuf>,
+    report: Option<PathBuf>,
+    plies: usize,
+    min_games: u32,
+    min_rating: u32,
+    files: Vec<PathBuf>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        out: None,
+        report: None,
+        plies: 20,
+        min_games: 1,
+        min_rating: 0,
+        files: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.files.push(arg.into());
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value of {arg}"))?;
+        let number = || {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid value '{value}' of {arg}"))
+        };
+        match arg.as_str() {
+            "--out" => options.out = Some(value.into()),
+            "--report" => options.report = Some(value.into()),
+            "--plies" => options.plies = number()? as usize,
+            "--min-games" => options.min_games = number()?,
+            "--min-rating" => options.min_rating = number()?,
+            _ => return Err(format!("Unknown option {arg}")),
+        }
+    }
+    if options.files.is_empty() {
+        return Err("No PGN files given".to_string());
+    }
+    Ok(options)
+}
+
+/// Results of the games in which a move was played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Record {
+    games: u32,
+    white_wins: u32,
+    draws: u32,
+    black_wins: u32,
+}
+
+impl Record {
+    const fn add(&mut self, result: GameResult) {
+        self.games += 1;
+        match result {
+            GameResult::WhiteWins => self.white_wins += 1,
+            GameResult::BlackWins => self.black_wins += 1,
+            GameResult::Draw => self.draws += 1,
+            GameResult::Unknown => {}
+        }
+    }
+
+    /// Wins and losses of `team`.
+    const fn wins_losses(&self, team: Team) -> (u32, u32) {
+        match team {
+            Team::White => (self.white_wins, self.black_wins),
+            Team::Black => (self.black_wins, self.white_wins),
+        }
+    }
+
+    /// Half points scored by `team`, a win counting 2 and a draw 1.
+    const fn half_points(&self, team: Team) -> u32 {
+        2 * self.wins_losses(team).0 + self.draws
+    }
+}
+
+struct MoveStats {
+    mv: Move,
+    /// Notation of the move in the first game playing it.
+    san: String,
+    record: Record,
+}
+
+/// Position reached in the games and the moves played from it.
+struct Node {
+    position: Position,
+    moves: Vec<MoveStats>,
+}
+
+/// Move statistics of the positions of the games, by Zobrist key.
+#[derive(Default)]
+struct Tree {
+    nodes: HashMap<u64, Node>,
+    games: usize,
+    /// Games replayed only up to an unreadable move.
+    truncated: usize,
+}
+
+impl Tree {
+    /// Adds the first `plies` moves of `game`.
+    fn add(&mut self, game: &Game, plies: usize) -> Result<(), String> {
+        let result = game.result();
+        let mut position = match game.tag("FEN") {
+            Some(fen) => fen.parse()?,
+            None => Position::default(),
+        };
+        self.games += 1;
+        for san in game.moves.iter().take(plies) {
+            let Ok((next, mv)) = position.play_san(san) else {
+                self.truncated += 1;
+                break;
+            };
+            let node = self
+                .nodes
+                .entry(position.hash_key())
+                .or_insert_with(|| Node {
+                    position,
+                    moves: Vec::new(),
+                });
+            let index = node
+                .moves
+                .iter()
+                .position(|stats| same_move(stats.mv, mv))
+                .unwrap_or_else(|| {
+                    node.moves.push(MoveStats {
+                        mv,
+                        san: san.trim_end_matches(['!', '?']).to_string(),
+                        record: Record::default(),
+                    });
+                    node.moves.len() - 1
+                });
+            node.moves[index].record.add(result);
+            position = next;
+        }
+        Ok(())
+    }
+
+    /// Moves of `position` played in at least `min_games` games, most played first.
+    fn moves(&self, position: &Position, min_games: u32) -> Vec<&MoveStats> {
+        let mut moves: Vec<_> = self
+            .nodes
+            .get(&position.hash_key())
+            .map(|node| node.moves.iter().collect())
+            .unwrap_or_default();
+        moves.retain(|stats| stats.record.games >= min_games);
+        moves.sort_by_key(|stats| std::cmp::Reverse(stats.record.games));
+        moves
+    }
+
+    /// Book of the moves played in at least `min_games` games, weighted by the
+    /// half points they scored and scaled to fit 16 bits. Moves that never
+    /// scored are left out.
+    fn book(&self, min_games: u32) -> Book {
+        let keys = PolyglotKeys::default();
+        let max = self
+            .nodes
+            .values()
+            .flat_map(|node| {
+                let team = node.position.team();
+                node.moves.iter().map(move |m| m.record.half_points(team))
+            })
+            .max()
+            .unwrap_or(0);
+        let divisor = max.div_ceil(u32::from(u16::MAX)).max(1);
+        let scale = |points: u32| u16::try_from(points / divisor).unwrap_or(u16::MAX);
+        let mut entries = Vec::new();
+        for node in self.nodes.values() {
+            let key = match &node.position {
+                Position::White(board) => keys.key(board),
+                Position::Black(board) => keys.key(board),
+            };
+            let mut moves: Vec<_> = self
+                .moves(&node.position, min_games)
+                .into_iter()
+                .map(|stats| {
+                    (
+                        stats.mv,
+                        scale(stats.record.half_points(node.position.team())),
+                    )
+                })
+                .filter(|&(_, weight)| weight > 0)
+                .collect();
+            moves.sort_by_key(|&(_, weight)| std::cmp::Reverse(weight));
+            entries.extend(
+                moves
+                    .into_iter()
+                    .map(|(mv, weight)| BookEntry::new(key, mv, weight)),
+            );
+        }
+        Book::new(entries, keys)
+    }
+
+    /// Opening tree from the start position, one move per line indented by ply,
+    /// with its games, wins, draws and losses and score of the side playing it.
+    fn report(&self, plies: usize, min_games: u32) -> String {
+        let mut report = String::new();
+        self.write_report(&mut report, &Position::default(), 0, plies, min_games);
+        report
+    }
+
+    fn write_report(
+        &self,
+        report: &mut String,
+        position: &Position,
+        ply: usize,
+        plies: usize,
+        min_games: u32,
+    ) {
+        if ply >= plies {
+            return;
+        }
+        let team = position.team();
+        for stats in self.moves(position, min_games) {
+            let record = stats.record;
+            let (wins, losses) = record.wins_losses(team);
+            let number = match team {
+                Team::White => format!("{}.", ply / 2 + 1),
+                Team::Black => format!("{}...", ply / 2 + 1),
+            };
+            let score = f64::from(record.half_points(team)) * 50.0 / f64::from(record.games);
+            let _ = writeln!(
+                report,
+                "{:indent$}{number} {:<8} {:>6} games  +{wins} ={} -{losses}  {score:.1}%",
+                "",
+                stats.san,
+                record.games,
+                record.draws,
+                indent = 2 * ply
+            );
+            if let Ok(next) = position.play(&stats.mv.to_string()) {
+                self.write_report(report, &next, ply + 1, plies, min_games);
+            }
+        }
+    }
+}
+
+fn same_move(a: Move, b: Move) -> bool {
+    a.from().pos() == b.from().pos() && a.to().pos() == b.to().pos()
+}
+
+/// Whether both players of `game` are rated at least `min_rating`.
+fn rated(game: &Game, min_rating: u32) -> bool {
+    min_rating == 0
+        || ["WhiteElo", "BlackElo"].iter().all(|tag| {
+            game.tag(tag)
+                .and_then(|elo| elo.parse::<u32>().ok())
+                .is_some_and(|elo| elo >= min_rating)
+        })
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut tree = Tree::default();
+    let mut skipped = 0;
+    for file in &options.files {
+        let pgn = fs::read_to_string(file).map_err(|err| format!("{}: {err}", file.display()))?;
+        for game in pgn::parse(&pgn).map_err(|err| format!("{}: {err}", file.display()))? {
+            if game.result() == GameResult::Unknown || !rated(&game, options.min_rating) {
+                skipped += 1;
+            } else if let Err(err) = tree.add(&game, options.plies) {
+                eprintln!("{}: {err}", file.display());
+                skipped += 1;
+            }
+        }
+    }
+    eprintln!(
+        "{} games used, {} of them up to a castling, promotion or illegal move, {skipped} skipped, {} positions",
+        tree.games,
+        tree.truncated,
+        tree.nodes.len()
+    );
+
+    if let Some(out) = &options.out {
+        let book = tree.book(options.min_games);
+        let bytes = book.to_bytes();
+        fs::write(out, &bytes).map_err(|err| format!("{}: {err}", out.display()))?;
+        eprintln!("{} entries written to {}", bytes.len() / 16, out.display());
+    }
+    let report = tree.report(options.plies, options.min_games);
+    match &options.report {
+        Some(path) => fs::write(path, report).map_err(|err| format!("{}: {err}", path.display())),
+        None if options.out.is_none() => {
+            print!("{report}");
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1)).and_then(|options| run(&options));
+    if let Err(err) = result {
+        eprintln!("{err}\n{USAGE}");
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMES: &str = concat!(
+        "[WhiteElo \"2400\"]\n[BlackElo \"2300\"]\n[Result \"1-0\"]\n\n",
+        "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. O-O Nf6 1-0\n\n",
+        "[WhiteElo \"2400\"]\n[BlackElo \"1800\"]\n[Result \"1/2-1/2\"]\n\n",
+        "1. e4 c5 2. Nf3 d6 1/2-1/2\n\n",
+        "[WhiteElo \"2500\"]\n[BlackElo \"2500\"]\n[Result \"0-1\"]\n\n",
+        "1. d4 d5 0-1\n",
+    );
+
+    fn tree(min_rating: u32) -> Tree {
+        let mut tree = Tree::default();
+        for game in pgn::parse(GAMES).unwrap_or_default() {
+            if rated(&game, min_rating) {
+                assert!(tree.add(&game, 8).is_ok());
+            }
+        }
+        tree
+    }
+
+    #[test]
+    fn statistics_and_report() {
+        let tree = tree(0);
+        assert_eq!((tree.games, tree.truncated), (3, 1));
+        let start = Position::default();
+        let moves = tree.moves(&start, 1);
+        assert_eq!(moves[0].san, "e4");
+        assert_eq!(
+            moves[0].record,
+            Record {
+                games: 2,
+                white_wins: 1,
+                draws: 1,
+                black_wins: 0
+            }
+        );
+        assert_eq!(tree.moves(&start, 2).len(), 1);
+        assert_eq!(self::tree(2000).games, 2);
+
+        let report = tree.report(2, 2);
+        assert_eq!(report, "1. e4            2 games  +1 =1 -0  75.0%\n");
+        assert_eq!(tree.report(8, 1).lines().count(), 6 + 3 + 2);
+    }
+
+    #[test]
+    fn book_of_the_example_games() {
+        let mut tree = Tree::default();
+        for game in pgn::parse(include_str!("../../examples/openings.pgn")).unwrap_or_default() {
+            assert!(tree.add(&game, 20).is_ok());
+        }
+        let bytes = tree.book(1).to_bytes();
+        let Ok(book) = Book::from_bytes(&bytes, PolyglotKeys::default()) else {
+            panic!("written book does not read back");
+        };
+        let start = chess::Board::default();
+        let entries = book.entries(&start);
+        assert!(!entries.is_empty());
+        // reference key of the start position in the Polyglot specification
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.key == 0x463b_9618_1691_fc9c)
+        );
+        assert_eq!(
+            book.best_move(&start).map(|mv| mv.data().to_string()),
+            Some("e2e4".to_string())
+        );
+    }
+
+    #[test]
+    fn arguments() {
+        let args = |args: &str| parse_args(args.split_whitespace().map(String::from));
+        let options = args("--plies 8 --min-games 3 a.pgn b.pgn");
+        assert_eq!(options.as_ref().map(|o| o.plies), Ok(8));
+        assert_eq!(options.map(|o| o.files.len()), Ok(2));
+        assert!(args("--out book.bin").is_err());
+        assert!(args("--plies").is_err());
+        assert!(args("--plies x a.pgn").is_err());
+        assert!(args("--min-games 2").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Checks a chess problem: prints every solution with its duals.
+//!
+//! Usage: `chess-problem STIPULATION FEN`, e.g. `chess-problem '#2'
+//! 'k7/8/2K5/8/8/8/8/7R w'`. Stipulations are `#N`, `h#N` and `s#N`.
+
+use std::{env, process};
+
+use chess::Position;
+use chess::problem::{Problem, Solution, solve};
+
+const USAGE: &str = "Usage: chess-problem STIPULATION FEN";
+
+fn solutions(position: &Position, problem: Problem) -> Vec<Solution> {
+    match position {
+        Position::White(board) => solve(board, problem),
+        Position::Black(board) => solve(board, problem),
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let [problem, fen @ ..] = args else {
+        return Err("Missing stipulation".to_string());
+    };
+    let problem: Problem = problem.parse()?;
+    let position: Position = fen.join(" ").parse()?;
+    let solutions = solutions(&position, problem);
+    for solution in &solutions {
+        let line: Vec<String> = solution.line.iter().map(ToString::to_string).collect();
+        if solution.moves < problem.moves {
+            println!("{} (short, {} moves)", line.join(" "), solution.moves);
+        } else {
+            println!("{}", line.join(" "));
+        }
+        for dual in &solution.duals {
+            let continuations: Vec<String> =
+                dual.continuations.iter().map(ToString::to_string).collect();
+            println!("  dual after {}: {}", dual.defence, continuations.join(" "));
+        }
+    }
+    match solutions.len() {
+        0 => println!("{problem}: no solution"),
+        1 if solutions[0].duals.is_empty() => println!("{problem}: sound"),
+        1 => println!("{problem}: one solution with duals"),
+        count => println!("{problem}: cooked, {count} solutions"),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{err}\n{USAGE}");
+        process::exit(1);
+    }
+}
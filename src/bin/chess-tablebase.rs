@@ -0,0 +1,53 @@
+//! Generates endgame tables of a lone king against up to two pieces.
+//!
+//! Usage: `chess-tablebase DIR MATERIAL...`, e.g. `chess-tablebase tables KQK
+//! KRK KPK KBNK`, writes one file per material into `DIR`.
+
+use std::path::Path;
+use std::time::Instant;
+use std::{env, fs, process};
+
+use chess::endgame::{EXTENSION, EndgameTable, Material};
+
+const USAGE: &str = "Usage: chess-tablebase DIR MATERIAL...";
+
+fn run(args: &[String]) -> Result<(), String> {
+    let [dir, materials @ ..] = args else {
+        return Err("Missing directory".to_string());
+    };
+    if materials.is_empty() {
+        return Err("Missing material, e.g. KQK".to_string());
+    }
+    let materials = materials
+        .iter()
+        .map(|material| material.parse())
+        .collect::<Result<Vec<Material>, _>>()?;
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    for material in materials {
+        let start = Instant::now();
+        let table = EndgameTable::generate(&material);
+        let path = dir.join(format!("{material}.{EXTENSION}"));
+        let bytes = table.to_bytes();
+        fs::write(&path, &bytes).map_err(|err| format!("{}: {err}", path.display()))?;
+        let longest = table.longest_mate().map_or_else(
+            || "no mates".to_string(),
+            |plies| format!("longest mate {plies} plies"),
+        );
+        println!(
+            "{material}: {longest}, {} bytes written to {} in {:.1?}",
+            bytes.len(),
+            path.display(),
+            start.elapsed()
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{err}\n{USAGE}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,17 @@
+//! Engine speaking the Universal Chess Interface over stdin and stdout, or the
+//! Chess Engine Communication Protocol of XBoard when the first command is
+//! `xboard`.
+
+mod uci;
+mod xboard;
+
+use std::io::{self, BufRead};
+
+fn main() {
+    let mut lines = io::stdin().lock().lines().map_while(Result::ok).peekable();
+    if lines.peek().is_some_and(|line| line.trim() == "xboard") {
+        xboard::run(lines);
+    } else {
+        uci::run(lines);
+    }
+}
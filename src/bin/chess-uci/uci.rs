@@ -0,0 +1,382 @@
+//! Universal Chess Interface.
+//!
+//! A `quit` stops a running search, while the end of input lets it finish, so
+//! that scripted transcripts print the result of their last `go`. Commands
+//! changing the searcher, `go`, `setoption` and `ucinewgame`, are rejected while
+//! a search runs instead of waiting for it, the interface has to send `stop`
+//! first.
+
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chess::book::{Book, PolyglotKeys};
+use chess::endgame::Endgames;
+use chess::search::{Clock, DEFAULT_HASH_MB, SearchInfo, SearchLimits, Searcher};
+use chess::{Board, MAX_MOVES, Move, Position, Side, Team};
+
+const MAX_HASH_MB: usize = 65_536;
+const MAX_THREADS: usize = 256;
+
+/// Parameters of a `go` command.
+#[derive(Clone, Copy, Debug, Default)]
+struct Go {
+    limits: SearchLimits,
+    /// Search until `stop`, even when a limit ends the search earlier.
+    infinite: bool,
+}
+
+/// Parses the arguments of `position`, e.g. "startpos moves e2e4 e7e5".
+fn parse_position(args: &str) -> Result<Position, String> {
+    let (setup, moves) = args.split_once("moves").unwrap_or((args, ""));
+    let setup = setup.trim();
+    let mut position = if setup == "startpos" {
+        Position::default()
+    } else if let Some(fen) = setup.strip_prefix("fen") {
+        Position::from_str(fen.trim())?
+    } else {
+        return Err(format!("Unknown position '{setup}'"));
+    };
+    for mv in moves.split_whitespace() {
+        position = position.play(mv)?;
+    }
+    Ok(position)
+}
+
+/// Parses the arguments of `go` for a search of `team`.
+fn parse_go(args: &str, team: Team) -> Result<Go, String> {
+    let mut go = Go::default();
+    let mut clock = Clock::default();
+    let mut has_clock = false;
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token == "infinite" {
+            go.infinite = true;
+            continue;
+        }
+        if token == "ponder" {
+            continue;
+        }
+        let value = tokens
+            .next()
+            .ok_or_else(|| format!("Missing value of '{token}'"))?;
+        let value = value
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid value '{value}' of '{token}'"))?;
+        let millis = Duration::from_millis(value);
+        match (token, team) {
+            ("depth", _) => go.limits.depth = Some(i32::try_from(value).unwrap_or(i32::MAX)),
+            ("nodes", _) => go.limits.nodes = Some(value),
+            ("movetime", _) => go.limits.time = Some(millis),
+            ("movestogo", _) => clock.moves_to_go = Some(u32::try_from(value).unwrap_or(u32::MAX)),
+            ("wtime", Team::White) | ("btime", Team::Black) => {
+                clock.remaining = millis;
+                has_clock = true;
+            }
+            ("btime", Team::White) | ("wtime", Team::Black) => clock.opponent = Some(millis),
+            ("winc", Team::White) | ("binc", Team::Black) => clock.increment = millis,
+            ("winc" | "binc", _) => {}
+            _ => return Err(format!("Unknown go parameter '{token}'")),
+        }
+    }
+    if has_clock {
+        go.limits.clock = Some(clock);
+    }
+    Ok(go)
+}
+
+fn print_info(info: &SearchInfo<'_>) {
+    let millis = info.time.as_millis().max(1);
+    let nps = u128::from(info.nodes) * 1000 / millis;
+    for (i, line) in info.lines.iter().enumerate() {
+        let pv: Vec<_> = line.pv.iter().map(ToString::to_string).collect();
+        println!(
+            "info depth {} multipv {} score {} nodes {} nps {nps} time {millis} pv {}",
+            line.depth,
+            i + 1,
+            line.score,
+            info.nodes,
+            pv.join(" ")
+        );
+    }
+}
+
+fn search<S: Side + Clone>(
+    searcher: &mut Searcher,
+    board: &Board<S>,
+    limits: SearchLimits,
+) -> Option<Move> {
+    let result = searcher.search_with_info(board, limits, print_info);
+    result.best_move.map(|mv| mv.data())
+}
+
+struct Engine {
+    position: Position,
+    /// `None` while a search runs on another thread.
+    searcher: Option<Searcher>,
+    search: Option<JoinHandle<Searcher>>,
+    /// Whether the running search only ends on `stop`.
+    endless: bool,
+    stop: Arc<AtomicBool>,
+    own_book: bool,
+    book: Option<Book>,
+}
+
+impl Engine {
+    fn new() -> Self {
+        let searcher = Searcher::new();
+        Self {
+            position: Position::default(),
+            stop: searcher.stop_flag(),
+            searcher: Some(searcher),
+            search: None,
+            endless: false,
+            own_book: false,
+            book: None,
+        }
+    }
+
+    /// Waits for a running search to finish and returns the searcher.
+    fn searcher(&mut self) -> &mut Searcher {
+        if let Some(search) = self.search.take() {
+            self.searcher = search.join().ok();
+        }
+        self.searcher.get_or_insert_with(|| {
+            let searcher = Searcher::new();
+            self.stop = searcher.stop_flag();
+            searcher
+        })
+    }
+
+    /// Whether a search runs on another thread.
+    fn searching(&self) -> bool {
+        self.search
+            .as_ref()
+            .is_some_and(|search| !search.is_finished())
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.searcher();
+    }
+
+    /// Handles one command, returns `false` on `quit`.
+    fn handle(&mut self, line: &str) -> bool {
+        let (command, args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        if matches!(command, "go" | "setoption" | "ucinewgame") && self.searching() {
+            println!("info string Search running, '{command}' ignored until stop");
+            return true;
+        }
+        match command {
+            "uci" => {
+                println!(
+                    "id name {} {}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                );
+                println!("id author the {} authors", env!("CARGO_PKG_NAME"));
+                println!(
+                    "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                );
+                println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
+                println!("option name MultiPV type spin default 1 min 1 max {MAX_MOVES}");
+                println!("option name OwnBook type check default false");
+                println!("option name BookFile type string default <empty>");
+                println!("option name TablebasePath type string default <empty>");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.searcher().clear();
+                self.position = Position::default();
+            }
+            "position" => match parse_position(args) {
+                Ok(position) => self.position = position,
+                Err(err) => println!("info string {err}"),
+            },
+            "go" => match parse_go(args, self.position.team()) {
+                Ok(go) => self.go(go),
+                Err(err) => println!("info string {err}"),
+            },
+            "setoption" => {
+                if let Err(err) = self.set_option(args) {
+                    println!("info string {err}");
+                }
+            }
+            "stop" => self.stop(),
+            "quit" => {
+                self.stop();
+                return false;
+            }
+            "" => {}
+            _ => println!("info string Unknown command '{command}'"),
+        }
+        true
+    }
+
+    fn set_option(&mut self, args: &str) -> Result<(), String> {
+        let args = args.trim().strip_prefix("name").unwrap_or(args).trim();
+        let (name, value) = args
+            .split_once(" value ")
+            .ok_or_else(|| format!("Missing value of option '{args}'"))?;
+        let value = value.trim();
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid value '{value}' of option '{name}'"))
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "hash" => self
+                .searcher()
+                .set_hash_size(number()?.clamp(1, MAX_HASH_MB)),
+            "threads" => self.searcher().set_threads(number()?.clamp(1, MAX_THREADS)),
+            "multipv" => self.searcher().set_multi_pv(number()?.clamp(1, MAX_MOVES)),
+            "ownbook" => self.own_book = value.eq_ignore_ascii_case("true"),
+            "bookfile" => {
+                self.book = None;
+                if value != "<empty>" {
+                    self.book = Some(Book::open(value, PolyglotKeys::default())?);
+                }
+            }
+            "tablebasepath" => {
+                let endgames = if value == "<empty>" {
+                    None
+                } else {
+                    Some(Endgames::open_dir(value)?)
+                };
+                self.searcher()
+                    .set_tablebase(endgames.map(|endgames| Arc::new(endgames) as _));
+            }
+            _ => return Err(format!("Unknown option '{name}'")),
+        }
+        Ok(())
+    }
+
+    /// Book move of the current position picked at random by weight.
+    fn book_move(&self) -> Option<Move> {
+        let book = self.book.as_ref().filter(|_| self.own_book)?;
+        let random = RandomState::new().build_hasher().finish();
+        match &self.position {
+            Position::White(board) => book.weighted_move(board, random).map(|mv| mv.data()),
+            Position::Black(board) => book.weighted_move(board, random).map(|mv| mv.data()),
+        }
+    }
+
+    fn go(&mut self, go: Go) {
+        self.searcher();
+        if !go.infinite
+            && let Some(mv) = self.book_move()
+        {
+            println!("bestmove {mv}");
+            return;
+        }
+        let Some(mut searcher) = self.searcher.take() else {
+            return;
+        };
+        let limits = go.limits;
+        self.endless = go.infinite
+            || (limits.depth.is_none()
+                && limits.nodes.is_none()
+                && limits.time.is_none()
+                && limits.clock.is_none());
+        self.stop.store(false, Ordering::Relaxed);
+        let position = self.position.clone();
+        let stop = Arc::clone(&self.stop);
+        self.search = Some(thread::spawn(move || {
+            let best_move = match &position {
+                Position::White(board) => search(&mut searcher, board, go.limits),
+                Position::Black(board) => search(&mut searcher, board, go.limits),
+            };
+            while go.infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            println!(
+                "bestmove {}",
+                best_move.map_or_else(|| "0000".to_string(), |mv| mv.to_string())
+            );
+            searcher
+        }));
+    }
+}
+
+/// Runs the engine on the commands in `lines` until `quit`.
+pub fn run(lines: impl Iterator<Item = String>) {
+    let mut engine = Engine::new();
+    for line in lines {
+        if !engine.handle(&line) {
+            return;
+        }
+    }
+    // end of input, let the last search finish unless it would never end
+    if engine.endless {
+        engine.stop();
+    }
+    engine.searcher();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_commands() {
+        assert_eq!(parse_position("startpos"), Ok(Position::default()));
+        let position = parse_position("startpos moves e2e4 e7e5 g1f3");
+        assert_eq!(
+            position.as_ref().map(Position::fen),
+            Ok("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 0 1".to_string())
+        );
+        let fen = parse_position(
+            "fen rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2 moves g1f3",
+        );
+        assert_eq!(fen, position);
+        assert!(parse_position("startpos moves e2e5").is_err());
+        assert!(parse_position("somewhere").is_err());
+    }
+
+    #[test]
+    fn go_commands() {
+        let go = parse_go(
+            "wtime 60000 btime 30000 winc 1000 binc 500 movestogo 20",
+            Team::Black,
+        );
+        assert_eq!(
+            go.map(|go| go.limits.clock),
+            Ok(Some(Clock {
+                remaining: Duration::from_secs(30),
+                increment: Duration::from_millis(500),
+                moves_to_go: Some(20),
+                opponent: Some(Duration::from_secs(60)),
+            }))
+        );
+        let go = parse_go("depth 6 movetime 250 infinite", Team::White);
+        assert!(
+            go.as_ref()
+                .is_ok_and(|go| go.infinite && go.limits.clock.is_none())
+        );
+        assert_eq!(go.as_ref().map(|go| go.limits.depth), Ok(Some(6)));
+        assert_eq!(
+            go.map(|go| go.limits.time),
+            Ok(Some(Duration::from_millis(250)))
+        );
+        assert!(parse_go("depth", Team::White).is_err());
+        assert!(parse_go("mate 3", Team::White).is_err());
+    }
+
+    #[test]
+    fn commands_during_a_search_do_not_wait() {
+        let mut engine = Engine::new();
+        engine.handle("go infinite");
+        for command in ["setoption name MultiPV value 2", "ucinewgame", "go depth 1"] {
+            assert!(engine.handle(command));
+            assert!(engine.searching(), "{command}");
+        }
+        assert!(engine.handle("isready"));
+        assert!(engine.handle("stop"));
+        assert!(!engine.searching());
+        assert!(engine.handle("setoption name MultiPV value 2"));
+    }
+}
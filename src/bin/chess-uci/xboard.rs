@@ -0,0 +1,458 @@
+//! Chess Engine Communication Protocol of XBoard, version 2.
+//!
+//! The engine thinks on another thread. While it thinks `?` and `quit` make it
+//! move now, and `force`, `result`, `new`, `setboard`, `undo` and `remove` stop
+//! it without moving. Other commands changing the game wait for its move. Time
+//! management looks at both clocks, the opponent's sent with `otim`.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chess::search::{Clock, Score, SearchInfo, SearchLimits, Searcher, TABLEBASE_CENTIPAWNS};
+use chess::{Board, Outcome, Position, Side, Team};
+
+/// Time control set by `level`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Level {
+    /// Moves per time control, 0 when the base time is for the whole game.
+    moves: u32,
+    base: Duration,
+    increment: Duration,
+}
+
+/// Time control until the interface sets one, 40 moves in 5 minutes.
+const DEFAULT_LEVEL: Level = Level {
+    moves: 40,
+    base: Duration::from_mins(5),
+    increment: Duration::ZERO,
+};
+
+/// Score reported for a mate in 0 moves, mates in N moves add or subtract N.
+const MATE_SCORE: i32 = 100_000;
+
+/// Parses the arguments of `level`, e.g. "40 5 0" or "0 2:30 1.5".
+fn parse_level(args: &str) -> Result<Level, String> {
+    let [moves, base, increment] = args.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!("Invalid level '{args}'"));
+    };
+    let moves = moves
+        .parse()
+        .map_err(|_| format!("Invalid moves per time control '{moves}'"))?;
+    let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+    let (Ok(minutes), Ok(seconds)) = (minutes.parse::<u64>(), seconds.parse::<u64>()) else {
+        return Err(format!("Invalid base time '{base}'"));
+    };
+    let increment = increment
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("Invalid increment '{increment}'"))?;
+    Ok(Level {
+        moves,
+        base: Duration::from_secs(minutes * 60 + seconds),
+        increment,
+    })
+}
+
+/// Parses the centiseconds on a clock sent with `time` and `otim`.
+fn parse_clock(args: &str) -> Option<Duration> {
+    let centiseconds = args.trim().parse::<u64>().ok()?;
+    Some(Duration::from_millis(centiseconds * 10))
+}
+
+/// Result command for a game ended on the board.
+const fn result(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Checkmate {
+            winner: Team::White,
+        } => "1-0 {White mates}",
+        Outcome::Checkmate {
+            winner: Team::Black,
+        } => "0-1 {Black mates}",
+        Outcome::Stalemate => "1/2-1/2 {Stalemate}",
+    }
+}
+
+/// Prints thinking output: depth, score, time in centiseconds, nodes and line.
+fn print_thinking(info: &SearchInfo<'_>) {
+    let Some(line) = info.lines.first() else {
+        return;
+    };
+    let score = match line.score {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(moves) if moves > 0 => MATE_SCORE + moves,
+        Score::Mate(moves) => -MATE_SCORE + moves,
+        Score::Tablebase(plies) if plies > 0 => TABLEBASE_CENTIPAWNS - plies,
+        Score::Tablebase(plies) => -TABLEBASE_CENTIPAWNS - plies,
+    };
+    let pv: Vec<_> = line.pv.iter().map(ToString::to_string).collect();
+    println!(
+        "{} {score} {} {} {}",
+        line.depth,
+        info.time.as_millis() / 10,
+        info.nodes,
+        pv.join(" ")
+    );
+}
+
+/// Searches `board` and returns the best move in coordinate notation.
+fn search<S: Side + Clone>(
+    searcher: &mut Searcher,
+    board: &Board<S>,
+    limits: SearchLimits,
+    post: bool,
+) -> Option<String> {
+    let result = searcher.search_with_info(board, limits, |info| {
+        if post {
+            print_thinking(info);
+        }
+    });
+    result.best_move.map(|mv| mv.data().to_string())
+}
+
+struct XBoard {
+    /// Positions of the game so far, the current one last.
+    history: Vec<Position>,
+    /// `None` while the engine thinks on another thread.
+    searcher: Option<Searcher>,
+    /// Thinking engine, it returns the searcher and the position after its
+    /// move, `None` when it did not move.
+    thinking: Option<JoinHandle<(Searcher, Option<Position>)>>,
+    stop: Arc<AtomicBool>,
+    /// Whether a stopped search drops its move.
+    cancel: Arc<AtomicBool>,
+    /// Side played by the engine, `None` in force mode.
+    engine: Option<Team>,
+    post: bool,
+    level: Level,
+    /// Clock of the engine, set by `time`.
+    time: Duration,
+    /// Clock of the opponent, set by `otim`.
+    opponent_time: Duration,
+    /// Index in `history` of the position where the time control started.
+    control_start: usize,
+}
+
+impl XBoard {
+    fn new() -> Self {
+        let searcher = Searcher::new();
+        Self {
+            history: vec![Position::default()],
+            stop: searcher.stop_flag(),
+            searcher: Some(searcher),
+            thinking: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            engine: Some(Team::Black),
+            post: false,
+            level: DEFAULT_LEVEL,
+            time: DEFAULT_LEVEL.base,
+            opponent_time: DEFAULT_LEVEL.base,
+            control_start: 0,
+        }
+    }
+
+    /// Waits for the thinking engine and records its move.
+    fn finish(&mut self) {
+        let Some(thinking) = self.thinking.take() else {
+            return;
+        };
+        if let Ok((searcher, moved)) = thinking.join() {
+            self.searcher = Some(searcher);
+            if let Some(position) = moved {
+                if position.outcome().is_some() {
+                    self.engine = None;
+                }
+                self.history.push(position);
+            }
+        }
+    }
+
+    /// Makes the thinking engine play the best move found so far.
+    fn move_now(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.finish();
+    }
+
+    /// Stops the thinking engine without moving, unless it already moved.
+    fn abort(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.move_now();
+    }
+
+    /// The searcher, once the engine has finished thinking.
+    fn searcher(&mut self) -> &mut Searcher {
+        self.finish();
+        self.searcher.get_or_insert_with(Searcher::new)
+    }
+
+    fn position(&self) -> &Position {
+        // the history never gets empty, `undo` and `remove` keep the first position
+        &self.history[self.history.len() - 1]
+    }
+
+    /// Handles one command, returns `false` on `quit`.
+    fn handle(&mut self, line: &str) -> bool {
+        let (command, args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        match command {
+            "protover" => println!(
+                "feature myname=\"{} {}\" setboard=1 usermove=1 ping=1 colors=0 sigint=0 sigterm=0 analyze=0 done=1",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            "new" => {
+                self.abort();
+                self.history = vec![Position::default()];
+                self.control_start = 0;
+                self.engine = Some(Team::Black);
+                self.time = self.level.base;
+                self.opponent_time = self.level.base;
+                self.searcher().clear();
+            }
+            "force" | "result" => {
+                self.abort();
+                self.engine = None;
+            }
+            "?" => self.move_now(),
+            "go" => {
+                self.finish();
+                self.engine = Some(self.position().team());
+                self.think();
+            }
+            "usermove" => {
+                self.finish();
+                match self.position().play(args.trim()) {
+                    Ok(position) => {
+                        self.history.push(position);
+                        if self.engine == Some(self.position().team()) {
+                            self.think();
+                        }
+                    }
+                    Err(_) => println!("Illegal move: {}", args.trim()),
+                }
+            }
+            "level" => match parse_level(args) {
+                Ok(level) => {
+                    self.finish();
+                    self.level = level;
+                    self.time = level.base;
+                    self.opponent_time = level.base;
+                    self.control_start = self.history.len() - 1;
+                }
+                Err(err) => println!("Error ({err}): level"),
+            },
+            "time" => match parse_clock(args) {
+                Some(time) => self.time = time,
+                None => println!("Error (invalid time): {args}"),
+            },
+            "otim" => match parse_clock(args) {
+                Some(time) => self.opponent_time = time,
+                None => println!("Error (invalid time): {args}"),
+            },
+            "undo" => {
+                self.abort();
+                self.take_back(1);
+            }
+            "remove" => {
+                self.abort();
+                self.take_back(2);
+            }
+            "setboard" => {
+                self.abort();
+                match Position::from_str(args) {
+                    Ok(position) => {
+                        self.history = vec![position];
+                        self.control_start = 0;
+                    }
+                    Err(err) => println!("tellusererror Illegal position: {err}"),
+                }
+            }
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => println!("pong {}", args.trim()),
+            "quit" => {
+                self.move_now();
+                return false;
+            }
+            // commands without effect on this engine
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "name" | "rating" | "" => {}
+            _ => println!("Error (unknown command): {command}"),
+        }
+        true
+    }
+
+    fn take_back(&mut self, plies: usize) {
+        let len = self.history.len().saturating_sub(plies).max(1);
+        self.history.truncate(len);
+        self.control_start = self.control_start.min(len - 1);
+    }
+
+    /// Clock of the engine for the next search, the engine being on move.
+    fn clock(&self) -> Clock {
+        let moves_to_go = (self.level.moves > 0).then(|| {
+            // moves of the engine since the time control started
+            let team = self.position().team();
+            let played = self.history[self.control_start..self.history.len() - 1]
+                .iter()
+                .filter(|position| position.team() == team)
+                .count();
+            let played = u32::try_from(played).unwrap_or(0);
+            self.level.moves - played % self.level.moves
+        });
+        Clock {
+            remaining: self.time,
+            increment: self.level.increment,
+            moves_to_go,
+            opponent: Some(self.opponent_time),
+        }
+    }
+
+    /// Starts searching the current position on another thread, which plays
+    /// the best move and reports the result when the game ends.
+    fn think(&mut self) {
+        let position = self.position().clone();
+        if let Some(outcome) = position.outcome() {
+            println!("{}", result(outcome));
+            self.engine = None;
+            return;
+        }
+        let limits = SearchLimits {
+            clock: Some(self.clock()),
+            ..SearchLimits::default()
+        };
+        let mut searcher = self.searcher.take().unwrap_or_default();
+        self.stop = searcher.stop_flag();
+        self.stop.store(false, Ordering::Relaxed);
+        self.cancel.store(false, Ordering::Relaxed);
+        let cancel = Arc::clone(&self.cancel);
+        let post = self.post;
+        self.thinking = Some(thread::spawn(move || {
+            let best_move = match &position {
+                Position::White(board) => search(&mut searcher, board, limits, post),
+                Position::Black(board) => search(&mut searcher, board, limits, post),
+            };
+            let moved = best_move
+                .filter(|_| !cancel.load(Ordering::Relaxed))
+                .and_then(|mv| {
+                    let position = position.play(&mv).ok()?;
+                    println!("move {mv}");
+                    if let Some(outcome) = position.outcome() {
+                        println!("{}", result(outcome));
+                    }
+                    Some(position)
+                });
+            (searcher, moved)
+        }));
+    }
+}
+
+/// Runs the engine on the commands in `lines` until `quit`. At the end of input
+/// the engine finishes thinking, so that transcripts print its last move.
+pub fn run(lines: impl Iterator<Item = String>) {
+    let mut xboard = XBoard::new();
+    for line in lines {
+        if !xboard.handle(&line) {
+            return;
+        }
+    }
+    xboard.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn levels() {
+        assert_eq!(parse_level("40 5 0"), Ok(DEFAULT_LEVEL));
+        assert_eq!(
+            parse_level("0 2:30 1.5"),
+            Ok(Level {
+                moves: 0,
+                base: Duration::from_secs(150),
+                increment: Duration::from_millis(1500),
+            })
+        );
+        assert!(parse_level("40 5").is_err());
+        assert!(parse_level("40 5 -1").is_err());
+    }
+
+    #[test]
+    fn force_mode_moves_and_take_backs() {
+        let mut xboard = XBoard::new();
+        for command in ["xboard", "protover 2", "new", "force", "usermove e2e4"] {
+            assert!(xboard.handle(command));
+        }
+        xboard.handle("usermove e7e5");
+        xboard.handle("usermove e2e4");
+        assert_eq!(xboard.history.len(), 3);
+        xboard.handle("undo");
+        assert_eq!(xboard.position().team(), Team::Black);
+        xboard.handle("remove");
+        assert_eq!(xboard.history.len(), 1);
+
+        xboard.handle("setboard 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        xboard.handle("level 0 0:05 0");
+        xboard.handle("go");
+        xboard.finish();
+        assert_eq!(xboard.engine, None);
+        assert_eq!(
+            xboard.position().outcome(),
+            Some(Outcome::Checkmate {
+                winner: Team::White
+            })
+        );
+        assert!(!xboard.handle("quit"));
+    }
+
+    #[test]
+    fn move_now_and_force_interrupt_thinking() {
+        let mut xboard = XBoard::new();
+        let start = Instant::now();
+        // a move may take up to an hour
+        xboard.handle("level 1 60 0");
+        xboard.handle("otim 360000");
+        xboard.handle("go");
+        assert!(xboard.thinking.is_some());
+        xboard.handle("?");
+        assert_eq!(xboard.history.len(), 2);
+        assert_eq!(xboard.engine, Some(Team::White));
+
+        xboard.handle("force");
+        xboard.handle("usermove e7e5");
+        xboard.handle("go");
+        xboard.handle("force");
+        assert_eq!(xboard.history.len(), 3);
+        assert_eq!(xboard.engine, None);
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert_eq!(xboard.opponent_time, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn moves_to_go_count_from_the_time_control() {
+        let mut xboard = XBoard::new();
+        let moves_to_go = |xboard: &XBoard| xboard.clock().moves_to_go;
+        xboard.handle("force");
+        xboard.handle("setboard 4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(moves_to_go(&xboard), Some(40));
+        // White has not moved yet after Black started
+        xboard.handle("usermove e8d8");
+        assert_eq!(moves_to_go(&xboard), Some(40));
+        xboard.handle("usermove e1d1");
+        assert_eq!(moves_to_go(&xboard), Some(39));
+        xboard.handle("level 40 5 0");
+        assert_eq!(moves_to_go(&xboard), Some(40));
+        xboard.handle("usermove d8e8");
+        xboard.handle("usermove d1e1");
+        assert_eq!(moves_to_go(&xboard), Some(39));
+        xboard.handle("remove");
+        assert_eq!(moves_to_go(&xboard), Some(40));
+        xboard.handle("level 0 5 0");
+        assert_eq!(moves_to_go(&xboard), None);
+    }
+}
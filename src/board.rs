@@ -1,14 +1,25 @@
-use std::collections::HashSet;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
-use crate::movement::{LegalMove, Move, PossibleMove};
+use crate::Position;
+use crate::attack::AttackMap;
+use crate::movement::{LegalMove, MAX_MOVES, Move, MoveList, PossibleMove};
 use crate::piece::Piece;
 use crate::piece::piece_type::PieceType;
-use crate::piece::team::{Side, Team, White};
+use crate::piece::team::{Black, Side, Team, White};
 use crate::square::{Pos, Square};
+use crate::zobrist;
 
 const EMPTY_SQUARE: &str = "  ";
+/// FEN of the starting position.
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// End of a game decided on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate { winner: Team },
+    Stalemate,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Board<S: Side + Clone> {
@@ -38,6 +49,29 @@ impl<S: Side + Clone> Board<S> {
         S::TEAM
     }
 
+    /// Zobrist key of the position, identifying it in hash tables.
+    #[must_use]
+    pub fn hash_key(&self) -> u64 {
+        let side = match S::TEAM {
+            Team::White => 0,
+            Team::Black => zobrist::BLACK_TO_MOVE,
+        };
+        Pos::all()
+            .filter_map(|pos| self.at(pos).map(|piece| (piece, pos)))
+            .fold(side, |key, (piece, pos)| {
+                key ^ zobrist::piece_key(piece, pos)
+            })
+    }
+
+    /// Zobrist key of the pawns of both teams, other pieces are left out.
+    #[must_use]
+    pub fn pawn_key(&self) -> u64 {
+        Pos::all()
+            .filter_map(|pos| self.at(pos).map(|piece| (piece, pos)))
+            .filter(|(piece, _)| piece.piece_type() == PieceType::Pawn)
+            .fold(0, |key, (piece, pos)| key ^ zobrist::piece_key(piece, pos))
+    }
+
     fn is_empty_between(&self, from: Pos, to: Pos) -> bool {
         let Ok(to_rank) = isize::try_from(to.rank()) else {
             return false;
@@ -78,98 +112,213 @@ impl<S: Side + Clone> Board<S> {
         true
     }
 
-    fn possible_moves(&self, sq: Square) -> Vec<PossibleMove> {
-        let Some(piece) = sq.content() else {
-            return vec![];
-        };
-
-        piece
-            .raw_moves(sq.pos())
-            .into_iter()
-            .filter(|raw_move| {
-                self.at(raw_move.to)
-                    .is_none_or(|to_piece| to_piece.team() != piece.team())
-            })
-            .filter(|raw_move| match piece.piece_type() {
-                PieceType::Rook | PieceType::Bishop | PieceType::Queen => {
-                    self.is_empty_between(sq.pos(), raw_move.to)
-                }
-                PieceType::Pawn => {
-                    if raw_move.from.file() == raw_move.to.file() {
-                        self.at(raw_move.to).is_none()
-                    } else {
-                        self.at(raw_move.to).is_some()
+    fn possible_moves(&self, sq: Square) -> impl Iterator<Item = PossibleMove> + '_ {
+        sq.content().into_iter().flat_map(move |piece| {
+            piece
+                .raw_moves(sq.pos())
+                .filter(move |raw_move| {
+                    self.at(raw_move.to)
+                        .is_none_or(|to_piece| to_piece.team() != piece.team())
+                })
+                .filter(move |raw_move| match piece.piece_type() {
+                    PieceType::Rook | PieceType::Bishop | PieceType::Queen => {
+                        self.is_empty_between(sq.pos(), raw_move.to)
                     }
-                }
-                _ => true,
-            })
-            .map(|raw_move| {
-                let from = Square::new(raw_move.from, self);
-                let to = Square::new(raw_move.to, self);
-                let move_data = match self.at(raw_move.to) {
-                    Some(_) => Move::Capture { from, to },
-                    None => Move::Simple { from, to },
-                };
-                PossibleMove::new(move_data)
-            })
-            .collect()
-    }
-
-    fn enumerate_pieces(&self, team: Team) -> HashSet<Square> {
-        let mut pieces = HashSet::new();
-        for rank in 0..8 {
-            for file in 0..8 {
-                if let Some(pos) = Pos::new(rank, file) {
-                    let sq = Square::new(pos, self);
-                    if let Some(piece) = sq.content()
-                        && piece.team() == team
-                    {
-                        pieces.insert(sq);
+                    PieceType::Pawn => {
+                        if raw_move.from.file() == raw_move.to.file() {
+                            self.at(raw_move.to).is_none()
+                                && self.is_empty_between(raw_move.from, raw_move.to)
+                        } else {
+                            self.at(raw_move.to).is_some()
+                        }
                     }
-                }
+                    _ => true,
+                })
+                .map(|raw_move| {
+                    let from = Square::new(raw_move.from, self);
+                    let to = Square::new(raw_move.to, self);
+                    let move_data = match self.at(raw_move.to) {
+                        Some(_) => Move::Capture { from, to },
+                        None => Move::Simple { from, to },
+                    };
+                    PossibleMove::new(move_data)
+                })
+        })
+    }
+
+    /// Number of moves of the piece on `sq` ignoring whether they leave its king in check.
+    pub(crate) fn mobility(&self, sq: Square) -> usize {
+        self.possible_moves(sq).count()
+    }
+
+    /// Squares holding pieces of `team` in order a1, b1, ..., h8.
+    fn enumerate_pieces(&self, team: Team) -> impl Iterator<Item = Square> + '_ {
+        Pos::all()
+            .map(|pos| Square::new(pos, self))
+            .filter(move |sq| sq.content().is_some_and(|piece| piece.team() == team))
+    }
+
+    fn attacks(&self, from: Pos, piece: Piece, to: Pos) -> bool {
+        if from == to {
+            return false;
+        }
+        let rank_diff = from.rank().abs_diff(to.rank());
+        let file_diff = from.file().abs_diff(to.file());
+        match piece.piece_type() {
+            PieceType::King => rank_diff <= 1 && file_diff <= 1,
+            PieceType::Knight => rank_diff * file_diff == 2,
+            PieceType::Pawn => [1, -1]
+                .into_iter()
+                .any(|side| from.checked_add((piece.team().direction(), side)) == Some(to)),
+            PieceType::Rook => {
+                (rank_diff == 0 || file_diff == 0) && self.is_empty_between(from, to)
+            }
+            PieceType::Bishop => rank_diff == file_diff && self.is_empty_between(from, to),
+            PieceType::Queen => {
+                (rank_diff == 0 || file_diff == 0 || rank_diff == file_diff)
+                    && self.is_empty_between(from, to)
             }
         }
-        pieces
     }
 
-    fn attacked_squares(&self, team: Team) -> HashSet<Square> {
-        let team_pieces = self.enumerate_pieces(team);
-        let mut attacked = HashSet::new();
-        for sq in team_pieces {
-            let moves = self.possible_moves(sq);
-            for mv in moves {
-                attacked.insert(mv.data().to());
+    /// Squares with pieces of `team` attacking `pos`, whatever stands on `pos`.
+    pub fn attackers_of(&self, pos: Pos, team: Team) -> impl Iterator<Item = Square> + '_ {
+        Pos::all()
+            .map(|from| Square::new(from, self))
+            .filter(move |sq| {
+                sq.content()
+                    .is_some_and(|piece| piece.team() == team && self.attacks(sq.pos(), piece, pos))
+            })
+    }
+
+    #[must_use]
+    pub fn is_attacked(&self, pos: Pos, by: Team) -> bool {
+        self.attackers_of(pos, by).next().is_some()
+    }
+
+    #[must_use]
+    pub fn attack_map(&self) -> AttackMap {
+        let mut map = AttackMap::default();
+        for from in Pos::all() {
+            let Some(piece) = self.at(from) else {
+                continue;
+            };
+            for to in Pos::all() {
+                if self.attacks(from, piece, to) {
+                    map.add(to, piece.team());
+                }
             }
         }
+        map
+    }
 
-        attacked
+    fn least_valuable_attacker(&self, pos: Pos, team: Team) -> Option<Square> {
+        self.attackers_of(pos, team).min_by_key(|sq| {
+            sq.content()
+                .map_or(i32::MAX, |piece| piece.piece_type().value())
+        })
     }
 
+    /// Static exchange evaluation of `mv`.
+    ///
+    /// Both sides keep recapturing on the destination square with their least
+    /// valuable attacker, revealing x-ray attackers behind moved sliders, and each
+    /// side may stop once recapturing stops paying off. Returns the net material
+    /// won by the side on move in centipawns.
     #[must_use]
-    pub fn legal_moves_sq(&self, sq: Square) -> Vec<LegalMove<S>> {
-        if let Some(piece) = sq.content()
-            && piece.team() == S::TEAM
+    pub fn see(&self, mv: &LegalMove<S>) -> i32 {
+        const MAX_EXCHANGES: usize = 32;
+
+        let target = mv.data().to().pos();
+        let Some(mut on_target) = mv.data().from().content() else {
+            return 0;
+        };
+        let mut board = self.clone();
+        board.set(mv.data().from().pos(), None);
+
+        let mut gain = [0; MAX_EXCHANGES];
+        gain[0] = mv
+            .data()
+            .to()
+            .content()
+            .map_or(0, |piece| piece.piece_type().value());
+        let mut depth = 0;
+        let mut team = S::TEAM.enemy();
+        while depth + 1 < MAX_EXCHANGES
+            && let Some(attacker) = board.least_valuable_attacker(target, team)
+            && let Some(piece) = attacker.content()
         {
-            self.possible_moves(sq)
-                .into_iter()
-                .filter(|m| self.is_legal(m))
-                .map(|m| LegalMove::new(m.data()))
-                .collect()
-        } else {
-            vec![]
+            depth += 1;
+            gain[depth] = on_target.piece_type().value() - gain[depth - 1];
+            board.set(attacker.pos(), None);
+            on_target = piece;
+            team = team.enemy();
+        }
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
         }
+        gain[0]
     }
 
+    fn king(&self, team: Team) -> Option<Pos> {
+        Pos::all().find(|&pos| {
+            self.at(pos)
+                .is_some_and(|p| p.team() == team && p.piece_type() == PieceType::King)
+        })
+    }
+
+    /// Whether the king of the side on move is attacked.
     #[must_use]
-    pub fn team_legal_moves(&self) -> Vec<LegalMove<S>> {
-        let team_pieces = self.enumerate_pieces(S::TEAM);
-        let mut moves = vec![];
-        for sq in team_pieces {
-            for mv in self.legal_moves_sq(sq) {
-                moves.push(mv);
-            }
+    pub fn is_in_check(&self) -> bool {
+        self.king(S::TEAM)
+            .is_some_and(|king| self.is_attacked(king, S::TEAM.enemy()))
+    }
+
+    /// Result of the game when the side on move has no legal moves, `None` while
+    /// it can move.
+    #[must_use]
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.legal_moves().next().is_some() {
+            None
+        } else if self.is_in_check() {
+            Some(Outcome::Checkmate {
+                winner: S::TEAM.enemy(),
+            })
+        } else {
+            Some(Outcome::Stalemate)
         }
+    }
+
+    fn legal_moves_from(&self, sq: Square) -> impl Iterator<Item = LegalMove<S>> + '_ {
+        let own = sq.content().is_some_and(|piece| piece.team() == S::TEAM);
+        self.possible_moves(sq)
+            .filter(move |m| own && self.is_legal(m))
+            .map(|m| LegalMove::new(m.data()))
+    }
 
+    /// Legal moves of the piece on `sq`, ordered by destination square.
+    #[must_use]
+    pub fn legal_moves_sq(&self, sq: Square) -> MoveList<S> {
+        let mut moves: MoveList<S> = self.legal_moves_from(sq).collect();
+        moves.sort_by_squares();
+        moves
+    }
+
+    /// Lazily generated legal moves of the side on move, by starting square in
+    /// order a1, b1, ..., h8. Moves of one piece come in the order of its move
+    /// pattern, use [`Board::team_legal_moves`] for a list ordered by destination.
+    pub fn legal_moves(&self) -> impl Iterator<Item = LegalMove<S>> + '_ {
+        self.enumerate_pieces(S::TEAM)
+            .flat_map(|sq| self.legal_moves_from(sq))
+    }
+
+    /// Legal moves of the side on move, ordered by starting square a1, b1, ..., h8
+    /// and then by destination square.
+    #[must_use]
+    pub fn team_legal_moves(&self) -> MoveList<S> {
+        let mut moves: MoveList<S> = self.legal_moves().collect();
+        moves.sort_by_squares();
         moves
     }
 
@@ -183,14 +332,9 @@ impl<S: Side + Clone> Board<S> {
         new_board.set(mv.data().from().pos(), None);
         new_board.set(mv.data().to().pos(), mv.data().from().content());
 
-        let attacked = new_board.attacked_squares(piece.team().enemy());
-        if attacked.iter().any(|sq| {
-            sq.content()
-                .is_some_and(|p| p.piece_type() == PieceType::King)
-        }) {
-            return false;
-        }
-        true
+        new_board
+            .king(piece.team())
+            .is_none_or(|king| !new_board.is_attacked(king, piece.team().enemy()))
     }
 
     #[must_use]
@@ -207,8 +351,271 @@ impl<S: Side + Clone> Board<S> {
         );
         new_board
     }
+
+    /// Reads a position in Forsyth-Edwards Notation with `S` on move. The game
+    /// knows no castling, castling rights only mark their king and rook as
+    /// unmoved, see [`Board::castling_rights`]. The en passant square and the
+    /// move counters are ignored.
+    ///
+    /// # Errors
+    ///
+    /// When the FEN is malformed, has the other side on move or has so many
+    /// pieces that a side could have more than [`MAX_MOVES`] moves, which no
+    /// game reaches.
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().ok_or("Empty FEN")?;
+        let side = fields.next().unwrap_or("w");
+        let castling = fields.next().unwrap_or("-");
+        let expected = match S::TEAM {
+            Team::White => "w",
+            Team::Black => "b",
+        };
+        if side != expected {
+            return Err(format!("FEN has '{side}' on move, expected '{expected}'"));
+        }
+
+        let ranks: Vec<_> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("FEN must have 8 ranks, found {}", ranks.len()));
+        }
+        let mut board = Self::new();
+        for (rank, row) in (0..8).rev().zip(ranks) {
+            let mut file = 0;
+            for c in row.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as usize;
+                } else {
+                    let piece = Piece::from_fen_char(c)
+                        .ok_or_else(|| format!("Unknown piece '{c}' in FEN"))?;
+                    if file < 8 {
+                        board.board[rank][file] = Some(if on_start_square(piece, rank, file) {
+                            piece
+                        } else {
+                            piece.touch_piece()
+                        });
+                    }
+                    file += 1;
+                }
+            }
+            if file != 8 {
+                return Err(format!("FEN rank {} must have 8 files", rank + 1));
+            }
+        }
+        if let Some(c) = castling.chars().find(|c| !"KQkq-".contains(*c)) {
+            return Err(format!("Unknown castling right '{c}' in FEN"));
+        }
+        // kings and rooks without a castling right have moved
+        let rights = ['K', 'Q', 'k', 'q'].map(|right| castling.contains(right));
+        for (i, (_, king, rook)) in CASTLING.into_iter().enumerate() {
+            if !rights[i] {
+                board.touch(rook);
+            }
+            // the other right of the team shares the king
+            if !rights[i] && !rights[i ^ 1] {
+                board.touch(king);
+            }
+        }
+        board.check_move_limit()?;
+        Ok(board)
+    }
+
+    /// Fails when the pieces of a side could have more than [`MAX_MOVES`]
+    /// moves, more than a [`MoveList`] holds.
+    fn check_move_limit(&self) -> Result<(), String> {
+        for team in [Team::White, Team::Black] {
+            let moves: usize = self
+                .board
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|piece| piece.team() == team)
+                .map(|piece| most_moves(piece.piece_type()))
+                .sum();
+            if moves > MAX_MOVES {
+                return Err(format!(
+                    "{team:?} could have {moves} moves, at most {MAX_MOVES} are supported"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the piece on `pos`, if any, as moved.
+    fn touch(&mut self, pos: Pos) {
+        self.set(pos, self.at(pos).map(Piece::touch_piece));
+    }
+
+    /// Castling rights of white short, white long, black short and black long
+    /// castling: whether their king and rook stand unmoved on their start
+    /// squares. The game knows no castling, the rights only complete FEN and
+    /// opening book keys.
+    #[must_use]
+    pub fn castling_rights(&self) -> [bool; 4] {
+        CASTLING.map(|(team, king, rook)| {
+            self.at(king) == Some(Piece::new(PieceType::King, team))
+                && self.at(rook) == Some(Piece::new(PieceType::Rook, team))
+        })
+    }
+
+    /// Forsyth-Edwards Notation of the position, without en passant square and
+    /// with the move counters of a first move.
+    #[must_use]
+    pub fn fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for square in self.board[rank] {
+                if let Some(piece) = square {
+                    if empty > 0 {
+                        fen += &empty.to_string();
+                        empty = 0;
+                    }
+                    fen.push(piece.fen_char());
+                } else {
+                    empty += 1;
+                }
+            }
+            if empty > 0 {
+                fen += &empty.to_string();
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+        let side = match S::TEAM {
+            Team::White => 'w',
+            Team::Black => 'b',
+        };
+        let castling: String = "KQkq"
+            .chars()
+            .zip(self.castling_rights())
+            .filter_map(|(right, kept)| kept.then_some(right))
+            .collect();
+        let castling = if castling.is_empty() { "-" } else { &castling };
+        format!("{fen} {side} {castling} - 0 1")
+    }
+
+    /// Legal move given in coordinate notation, e.g. "g1f3".
+    ///
+    /// # Errors
+    ///
+    /// When no legal move has that notation.
+    pub fn parse_move(&self, mv: &str) -> Result<LegalMove<S>, String> {
+        self.team_legal_moves()
+            .into_iter()
+            .find(|legal| legal.data().to_string() == mv)
+            .ok_or_else(|| format!("Illegal move '{mv}'"))
+    }
+
+    /// Legal move given in standard algebraic notation, e.g. "Nf3", "exd5" or
+    /// "R1e2+". Check marks and annotations are ignored.
+    ///
+    /// # Errors
+    ///
+    /// When the notation is malformed, matches no or several legal moves, or is a
+    /// castling or promotion, which the game does not know.
+    pub fn parse_san(&self, san: &str) -> Result<LegalMove<S>, String> {
+        let trimmed = san.trim_end_matches(['+', '#', '!', '?']);
+        if trimmed.starts_with("O-O") || trimmed.starts_with("0-0") {
+            return Err(format!("Castling '{san}' is not supported"));
+        }
+        if trimmed.contains('=') {
+            return Err(format!("Promotion '{san}' is not supported"));
+        }
+        let (piece_type, rest) = match trimmed.chars().next() {
+            Some(c) if c.is_ascii_uppercase() => (
+                PieceType::from_char(c).ok_or_else(|| format!("Unknown piece in '{san}'"))?,
+                &trimmed[1..],
+            ),
+            _ => (PieceType::Pawn, trimmed),
+        };
+        let split = rest
+            .len()
+            .checked_sub(2)
+            .filter(|&split| rest.is_char_boundary(split))
+            .ok_or_else(|| format!("Malformed move '{san}'"))?;
+        let to: Pos = rest[split..].parse()?;
+        let hints = rest[..split].trim_end_matches('x');
+        let mut candidates = self.team_legal_moves().into_iter().filter(|mv| {
+            let from = mv.data().from();
+            from.content()
+                .is_some_and(|piece| piece.piece_type() == piece_type)
+                && mv.data().to().pos() == to
+                && hints.chars().all(|hint| match hint {
+                    'a'..='h' => from.pos().file() == hint as usize - 'a' as usize,
+                    '1'..='8' => from.pos().rank() == hint as usize - '1' as usize,
+                    _ => false,
+                })
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(mv),
+            (None, _) => Err(format!("Illegal move '{san}'")),
+            (Some(_), Some(_)) => Err(format!("Ambiguous move '{san}'")),
+        }
+    }
+
+    /// Board holding only `pieces`, those off their start square counted as moved
+    /// like in [`Board::from_fen`].
+    pub(crate) fn with_pieces(pieces: impl IntoIterator<Item = (Piece, Pos)>) -> Self {
+        let mut board = Self::new();
+        for (piece, pos) in pieces {
+            let unmoved = on_start_square(piece, pos.rank(), pos.file());
+            board.set(pos, Some(if unmoved { piece } else { piece.touch_piece() }));
+        }
+        board
+    }
+
+    /// The same position with the other side on move, as if this side passed.
+    pub(crate) const fn null_move(&self) -> Board<S::Other> {
+        Board {
+            board: self.board,
+            _side: PhantomData,
+        }
+    }
 }
 
+/// Whether `piece` stands where a piece of its kind starts the game, FEN does not
+/// tell whether it moved away and back.
+const fn on_start_square(piece: Piece, rank: usize, file: usize) -> bool {
+    let (back_rank, pawn_rank) = match piece.team() {
+        Team::White => (0, 1),
+        Team::Black => (7, 6),
+    };
+    match piece.piece_type() {
+        PieceType::Pawn => rank == pawn_rank,
+        PieceType::Rook => rank == back_rank && (file == 0 || file == 7),
+        PieceType::Knight => rank == back_rank && (file == 1 || file == 6),
+        PieceType::Bishop => rank == back_rank && (file == 2 || file == 5),
+        PieceType::Queen => rank == back_rank && file == 3,
+        PieceType::King => rank == back_rank && file == 4,
+    }
+}
+
+/// Team, king and rook square of white short and long and black short and long
+/// castling.
+const CASTLING: [(Team, Pos, Pos); 4] = [
+    (Team::White, Pos::E1, Pos::H1),
+    (Team::White, Pos::E1, Pos::A1),
+    (Team::Black, Pos::E8, Pos::H8),
+    (Team::Black, Pos::E8, Pos::A8),
+];
+
+/// Most moves a piece of the type has on any board.
+const fn most_moves(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Queen => 27,
+        PieceType::Rook => 14,
+        PieceType::Bishop => 13,
+        PieceType::King | PieceType::Knight => 8,
+        PieceType::Pawn => 4,
+    }
+}
+
+/// Reads a board of comma separated squares, `wK` for the white king and two
+/// spaces for an empty square, one rank per line from a1 up. Like
+/// [`Board::from_fen`] it rejects boards where a side could have more than
+/// [`MAX_MOVES`] moves.
 impl<S: Side + Clone> FromStr for Board<S> {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -235,6 +642,7 @@ impl<S: Side + Clone> FromStr for Board<S> {
         if ranks.next().is_some() {
             return Err("Too many ranks!".to_string());
         }
+        b.check_move_limit()?;
 
         Ok(b)
     }
@@ -258,6 +666,21 @@ impl Default for Board<White> {
     }
 }
 
+impl<S: Side + Clone> From<&Board<S>> for Position {
+    fn from(board: &Board<S>) -> Self {
+        match S::TEAM {
+            Team::White => Self::White(Board::<White> {
+                board: board.board,
+                _side: PhantomData,
+            }),
+            Team::Black => Self::Black(Board::<Black> {
+                board: board.board,
+                _side: PhantomData,
+            }),
+        }
+    }
+}
+
 impl<S: Side + Clone> std::fmt::Display for Board<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut str = String::new();
@@ -285,6 +708,43 @@ impl<S: Side + Clone> std::fmt::Display for Board<S> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Black;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn outcomes() {
+        assert_eq!(Board::default().outcome(), None);
+        let mated = Board::<Black>::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b").unwrap();
+        assert_eq!(
+            mated.outcome(),
+            Some(Outcome::Checkmate {
+                winner: Team::White
+            })
+        );
+        let stalemate = Board::<Black>::from_fen("7k/5Q2/6K1/8/8/8/8/8 b").unwrap();
+        assert_eq!(stalemate.outcome(), Some(Outcome::Stalemate));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn fen_round_trip() {
+        let board = Board::<White>::from_fen(START_FEN).unwrap();
+        assert_eq!(board, Board::default());
+        assert_eq!(board.fen(), START_FEN);
+
+        let board = board.apply_move(&board.parse_move("g1f3").unwrap());
+        let fen = "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 0 1";
+        assert_eq!(board.fen(), fen);
+        assert_eq!(Board::from_fen(fen), Ok(board));
+
+        assert!(Board::<White>::from_fen(fen).is_err());
+        assert!(Board::<White>::from_fen("8/8/8/8/8/8/8 w").is_err());
+        assert!(Board::<White>::from_fen("9/8/8/8/8/8/8/8 w").is_err());
+        // nine queens could have more moves than a move list holds
+        assert!(Board::<White>::from_fen("QQQQQQQQ/Q7/8/8/8/8/8/k6K w").is_err());
+        assert!(Board::<White>::from_fen("QQQQQQQ1/8/8/8/8/8/8/k6K w").is_ok());
+        assert!(Board::<White>::default().parse_move("e2e5").is_err());
+    }
 
     #[test]
     fn default_board_does_not_panic() {
@@ -303,23 +763,146 @@ mod tests {
         assert!(default.is_empty_between(Pos::new(1, 5).unwrap(), Pos::new(5, 1).unwrap()));
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn attacks_on_default() {
+        let default = Board::default();
+        let f3 = Pos::new(2, 5).unwrap();
+        assert!(default.is_attacked(f3, Team::White));
+        assert!(!default.is_attacked(f3, Team::Black));
+        // pawns on e2 and g2, knight on g1
+        assert_eq!(default.attackers_of(f3, Team::White).count(), 3);
+        assert_eq!(default.attack_map().count(f3, Team::White), 3);
+        // pawn on d2 is defended by the knight, bishop, queen and king
+        let d2 = Pos::new(1, 3).unwrap();
+        assert_eq!(default.attack_map().count(d2, Team::White), 4);
+        assert!(!default.is_in_check());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn legal_moves_on_default() {
+        let default = Board::default();
+        let moves = default.team_legal_moves();
+        assert_eq!(moves.len(), 20);
+        // knight on b1 comes first, Nb1-a3 before Nb1-c3
+        assert_eq!(moves[0].data().from().pos(), Pos::new(0, 1).unwrap());
+        assert_eq!(moves[0].data().to().pos(), Pos::new(2, 0).unwrap());
+        assert_eq!(moves[1].data().to().pos(), Pos::new(2, 2).unwrap());
+        let replies: usize = moves
+            .iter()
+            .map(|mv| default.apply_move(mv).team_legal_moves().len())
+            .sum();
+        assert_eq!(replies, 400);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn pawn_double_step_is_blocked() {
+        // the double step once jumped over the square in front of the pawn
+        let blocked = Board::<White>::from_fen("4k3/8/8/8/8/4n3/4P3/4K3 w").unwrap();
+        assert!(blocked.parse_move("e2e3").is_err());
+        assert!(blocked.parse_move("e2e4").is_err());
+        let blocked = Board::<White>::from_fen("4k3/8/8/8/4n3/8/4P3/4K3 w").unwrap();
+        assert!(blocked.parse_move("e2e3").is_ok());
+        assert!(blocked.parse_move("e2e4").is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvv_lva_keeps_quiet_moves_in_square_order() {
+        let board = Board::<White>::from_fen("4k3/8/8/8/8/3p4/4P3/1N2K3 w").unwrap();
+        let mut moves = board.team_legal_moves();
+        moves.sort_mvv_lva();
+        let names: Vec<String> = moves.iter().map(|mv| mv.data().to_string()).collect();
+        // the pawn takes first, then the knight, king and pawn move in square order
+        assert_eq!(
+            names,
+            [
+                "e2d3", "b1d2", "b1a3", "b1c3", "e1d1", "e1f1", "e1d2", "e1f2", "e2e3", "e2e4"
+            ]
+        );
+    }
+
+    #[allow(clippy::unwrap_used)]
+    fn find_move<S: Side + Clone>(board: &Board<S>, from: Pos, to: Pos) -> LegalMove<S> {
+        board
+            .legal_moves_sq(Square::new(from, board))
+            .into_iter()
+            .find(|mv| mv.data().to().pos() == to)
+            .unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn see_exchanges() {
+        let board = Board::<White>::from_str(concat!(
+            "  ,  ,  ,wQ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,bP,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,bP,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  "
+        ))
+        .unwrap();
+        let d1 = Pos::new(0, 3).unwrap();
+        let d5 = Pos::new(4, 3).unwrap();
+        assert_eq!(board.see(&find_move(&board, d1, d5)), -800);
+
+        let mut moves = board.team_legal_moves();
+        assert!(!moves[0].data().is_capture());
+        moves.sort_mvv_lva();
+        assert_eq!(moves[0].data().to().pos(), d5);
+        assert!(!moves[1].data().is_capture());
+
+        // the rook on d1 backs up the capture through the rook on d2
+        let board = Board::<White>::from_str(concat!(
+            "  ,  ,  ,wR,  ,  ,  ,  \n",
+            "  ,  ,  ,wR,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,bP,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,bR,  ,  ,  ,  "
+        ))
+        .unwrap();
+        let d2 = Pos::new(1, 3).unwrap();
+        assert_eq!(board.see(&find_move(&board, d2, d5)), 100);
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn possible_moves() {
         let mut default = Board::default();
         assert!(
-            !dbg!(default.possible_moves(Square::new(Pos::new(1, 0).unwrap(), &default)))
-                .is_empty()
+            !dbg!(
+                default
+                    .possible_moves(Square::new(Pos::new(1, 0).unwrap(), &default))
+                    .collect::<Vec<_>>()
+            )
+            .is_empty()
         );
         assert!(
-            dbg!(default.possible_moves(Square::new(Pos::new(0, 0).unwrap(), &default))).is_empty()
+            dbg!(
+                default
+                    .possible_moves(Square::new(Pos::new(0, 0).unwrap(), &default))
+                    .collect::<Vec<_>>()
+            )
+            .is_empty()
         );
 
         default.set(Pos::new(1, 0).unwrap(), None);
 
         assert!(
-            !dbg!(default.possible_moves(Square::new(Pos::new(0, 0).unwrap(), &default)))
-                .is_empty()
+            !dbg!(
+                default
+                    .possible_moves(Square::new(Pos::new(0, 0).unwrap(), &default))
+                    .collect::<Vec<_>>()
+            )
+            .is_empty()
         );
     }
 }
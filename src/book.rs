@@ -0,0 +1,397 @@
+//! Reader and writer of Polyglot opening books.
+//!
+//! A book is a list of 16 byte big-endian entries sorted by position key: the
+//! key, the move, its weight and learning data. Keys are Zobrist keys built from
+//! the 781 numbers of the [`RANDOM64`] table of the Polyglot specification.
+//!
+//! Castling rights are those of [`Board::castling_rights`]. The game knows no en
+//! passant, so keys never include an en passant file and differ from the book's
+//! right after a double pawn step next to an enemy pawn.
+
+mod random64;
+
+pub use random64::RANDOM64;
+
+use std::fs;
+use std::path::Path;
+
+use crate::piece::team::Side;
+use crate::{Board, LegalMove, Move, Piece, PieceType, Pos, Team};
+
+/// Numbers in the Polyglot `Random64` table.
+pub const RANDOM64_LEN: usize = 781;
+const CASTLING_OFFSET: usize = 768;
+const TURN_OFFSET: usize = 780;
+/// Size of a book entry in bytes.
+const ENTRY_LEN: usize = 16;
+
+/// Polyglot `Random64` table, the Zobrist numbers of book keys. The default is
+/// the table of the specification, [`RANDOM64`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolyglotKeys {
+    keys: &'static [u64; RANDOM64_LEN],
+}
+
+impl Default for PolyglotKeys {
+    fn default() -> Self {
+        Self { keys: &RANDOM64 }
+    }
+}
+
+impl PolyglotKeys {
+    /// Polyglot key of `board`.
+    #[must_use]
+    pub fn key<S: Side + Clone>(&self, board: &Board<S>) -> u64 {
+        let mut key = Pos::all()
+            .filter_map(|pos| board.at(pos).map(|piece| (piece, pos)))
+            .fold(0, |key, (piece, pos)| {
+                key ^ self.keys[64 * piece_kind(piece) + pos.index()]
+            });
+        for (i, right) in board.castling_rights().into_iter().enumerate() {
+            if right {
+                key ^= self.keys[CASTLING_OFFSET + i];
+            }
+        }
+        if S::TEAM == Team::White {
+            key ^= self.keys[TURN_OFFSET];
+        }
+        key
+    }
+}
+
+/// Polyglot piece index, black pawn 0, white pawn 1, black knight 2, up to white
+/// king 11.
+const fn piece_kind(piece: Piece) -> usize {
+    let kind = match piece.piece_type() {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    match piece.team() {
+        Team::White => 2 * kind + 1,
+        Team::Black => 2 * kind,
+    }
+}
+
+/// Book entry of one move in one position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    /// Polyglot move: destination file and rank in bits 0-5, starting file and
+    /// rank in bits 6-11 and a promotion piece in bits 12-14.
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// Entry of `mv` in the position of `key`, without learning data.
+    #[must_use]
+    pub fn new(key: u64, mv: Move, weight: u16) -> Self {
+        let index = |pos: Pos| u16::try_from(pos.index()).unwrap_or_default();
+        Self {
+            key,
+            raw_move: index(mv.from().pos()) << 6 | index(mv.to().pos()),
+            weight,
+            learn: 0,
+        }
+    }
+
+    fn from_bytes(bytes: &[u8; ENTRY_LEN]) -> Self {
+        Self {
+            key: u64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()),
+            raw_move: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; ENTRY_LEN] {
+        let mut bytes = [0; ENTRY_LEN];
+        bytes[..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+
+    /// Starting and destination square of the move. Castling is encoded as the
+    /// king taking its own rook and decoded to the king's destination square.
+    #[must_use]
+    pub fn squares<S: Side + Clone>(&self, board: &Board<S>) -> Option<(Pos, Pos)> {
+        let index = |shift: u16| usize::from((self.raw_move >> shift) & 0o77);
+        let from = Pos::from_index(index(6))?;
+        let mut to = Pos::from_index(index(0))?;
+        let castles = board.at(from).is_some_and(|piece| {
+            piece.piece_type() == PieceType::King
+                && board
+                    .at(to)
+                    .is_some_and(|target| target.team() == piece.team())
+        });
+        if castles {
+            let file = if to.file() > from.file() { 6 } else { 2 };
+            to = Pos::from_index(from.index() - from.file() + file)?;
+        }
+        Some((from, to))
+    }
+
+    /// The legal move of the entry in `board`, `None` for promotions and castling,
+    /// which the game does not know.
+    #[must_use]
+    pub fn legal_move<S: Side + Clone>(&self, board: &Board<S>) -> Option<LegalMove<S>> {
+        if self.raw_move >> 12 != 0 {
+            return None;
+        }
+        let (from, to) = self.squares(board)?;
+        board
+            .team_legal_moves()
+            .into_iter()
+            .find(|mv| mv.data().from().pos() == from && mv.data().to().pos() == to)
+    }
+}
+
+/// Polyglot opening book.
+#[derive(Clone, Debug)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+    keys: PolyglotKeys,
+}
+
+impl Book {
+    /// Book of `entries`, which are sorted by key keeping the order of the moves
+    /// of a position.
+    #[must_use]
+    pub fn new(mut entries: Vec<BookEntry>, keys: PolyglotKeys) -> Self {
+        entries.sort_by_key(|entry| entry.key);
+        Self { entries, keys }
+    }
+
+    /// Reads a book from the bytes of a `.bin` file.
+    ///
+    /// # Errors
+    ///
+    /// When the length is not a whole number of entries.
+    pub fn from_bytes(bytes: &[u8], keys: PolyglotKeys) -> Result<Self, String> {
+        let (entries, rest) = bytes.as_chunks::<ENTRY_LEN>();
+        if !rest.is_empty() {
+            return Err(format!(
+                "Book size {} is not a multiple of {ENTRY_LEN}",
+                bytes.len()
+            ));
+        }
+        // books are sorted already, a stable sort keeps the order of their moves
+        Ok(Self::new(
+            entries.iter().map(BookEntry::from_bytes).collect(),
+            keys,
+        ))
+    }
+
+    /// Bytes of the book as a `.bin` file.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.to_bytes())
+            .collect()
+    }
+
+    /// # Errors
+    ///
+    /// When the file cannot be read or is not a book.
+    pub fn open(path: impl AsRef<Path>, keys: PolyglotKeys) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::from_bytes(&bytes, keys)
+    }
+
+    #[must_use]
+    pub const fn keys(&self) -> PolyglotKeys {
+        self.keys
+    }
+
+    /// Entries of the position of `board`, found by binary search.
+    #[must_use]
+    pub fn entries<S: Side + Clone>(&self, board: &Board<S>) -> &[BookEntry] {
+        let key = self.keys.key(board);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = start + self.entries[start..].partition_point(|entry| entry.key == key);
+        &self.entries[start..end]
+    }
+
+    /// Legal book moves of `board` with their weights, moves of weight 0 left out.
+    #[must_use]
+    pub fn moves<S: Side + Clone>(&self, board: &Board<S>) -> Vec<(LegalMove<S>, u16)> {
+        self.entries(board)
+            .iter()
+            .filter(|entry| entry.weight > 0)
+            .filter_map(|entry| Some((entry.legal_move(board)?, entry.weight)))
+            .collect()
+    }
+
+    /// Book move of the greatest weight.
+    #[must_use]
+    pub fn best_move<S: Side + Clone>(&self, board: &Board<S>) -> Option<LegalMove<S>> {
+        self.moves(board)
+            .into_iter()
+            .rev()
+            .max_by_key(|&(_, weight)| weight)
+            .map(|(mv, _)| mv)
+    }
+
+    /// Book move picked with a probability proportional to its weight, `random`
+    /// being a uniformly distributed number.
+    #[must_use]
+    pub fn weighted_move<S: Side + Clone>(
+        &self,
+        board: &Board<S>,
+        random: u64,
+    ) -> Option<LegalMove<S>> {
+        let moves = self.moves(board);
+        let total: u64 = moves.iter().map(|&(_, weight)| u64::from(weight)).sum();
+        let mut pick = random.checked_rem(total)?;
+        moves.into_iter().find_map(|(mv, weight)| {
+            if pick < u64::from(weight) {
+                Some(mv)
+            } else {
+                pick -= u64::from(weight);
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Black, White};
+
+    fn raw_move(from: &str, to: &str) -> u16 {
+        let index = |pos: &str| pos.parse::<Pos>().map_or(0, |pos| pos.index());
+        u16::try_from(index(from) << 6 | index(to)).unwrap_or(0)
+    }
+
+    fn book(entries: &[(u64, u16, u16)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for &(key, raw_move, weight) in entries {
+            bytes.extend(key.to_be_bytes());
+            bytes.extend(raw_move.to_be_bytes());
+            bytes.extend(weight.to_be_bytes());
+            bytes.extend(0_u32.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn keys_match_the_specification() {
+        // reference keys of the Polyglot specification
+        let keys = PolyglotKeys::default();
+        let start = Board::<White>::default();
+        assert_eq!(keys.key(&start), 0x463b_9618_1691_fc9c);
+        let e4 = start.apply_move(&start.parse_move("e2e4").unwrap());
+        assert_eq!(keys.key(&e4), 0x823c_9b50_fd11_4196);
+        let d5 = e4.apply_move(&e4.parse_move("d7d5").unwrap());
+        assert_eq!(keys.key(&d5), 0x0756_b944_61c5_0fb0);
+        // 1.e4 d5 2.e5 f5 3.Ke2, White has lost both castling rights
+        let ke2 = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3";
+        let ke2 = Board::<Black>::from_fen(ke2).unwrap();
+        assert_eq!(keys.key(&ke2), 0x652a_607c_a3f2_42c1);
+        let kf7 = ke2.apply_move(&ke2.parse_move("e8f7").unwrap());
+        assert_eq!(keys.key(&kf7), 0x00fd_d303_c946_bdd9);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn keys_take_castling_rights_from_the_fen() {
+        let keys = PolyglotKeys::default();
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1";
+        let board = Board::<White>::from_fen(fen).unwrap();
+        assert_eq!(board.castling_rights(), [false; 4]);
+        let castling = keys.keys[768..772].iter().fold(0, |key, k| key ^ k);
+        assert_eq!(keys.key(&board), 0x463b_9618_1691_fc9c ^ castling);
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kq - 0 1";
+        let board = Board::<White>::from_fen(fen).unwrap();
+        assert_eq!(
+            keys.key(&board),
+            0x463b_9618_1691_fc9c ^ keys.keys[769] ^ keys.keys[770]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn keys_follow_the_polyglot_layout() {
+        let keys = PolyglotKeys::default();
+        let board = Board::<White>::default();
+        let pieces = Pos::all()
+            .filter_map(|pos| {
+                board
+                    .at(pos)
+                    .map(|p| keys.keys[64 * piece_kind(p) + pos.index()])
+            })
+            .fold(0, |key, k| key ^ k);
+        let castling = keys.keys[768..772].iter().fold(0, |key, k| key ^ k);
+        assert_eq!(keys.key(&board), pieces ^ castling ^ keys.keys[780]);
+
+        // a king that moved away and back has lost both castling rights
+        let board = Board::<White>::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ").unwrap();
+        let moved = board.apply_move(&board.parse_move("e1e2").unwrap());
+        let moved = moved.apply_move(&moved.parse_move("e8e7").unwrap());
+        let back = moved.apply_move(&moved.parse_move("e2e1").unwrap());
+        let back = back.apply_move(&back.parse_move("e7e8").unwrap());
+        assert_eq!(
+            keys.key(&board) ^ keys.key(&back),
+            keys.keys[768] ^ keys.keys[769]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn book_moves() {
+        let keys = PolyglotKeys::default();
+        let start = Board::<White>::default();
+        let key = keys.key(&start);
+        let bytes = book(&[
+            (key.wrapping_add(1), raw_move("e7", "e5"), 10),
+            (key, raw_move("e2", "e4"), 30),
+            (key, raw_move("d2", "d4"), 10),
+            (key, raw_move("e1", "h1"), 50),
+            (key, raw_move("g1", "f3"), 0),
+            (key.wrapping_sub(1), raw_move("a2", "a3"), 10),
+        ]);
+        let book = Book::from_bytes(&bytes, keys).unwrap();
+        assert_eq!(book.entries(&start).len(), 4);
+        let castle = book.entries(&start)[2];
+        assert_eq!(
+            castle.squares(&start),
+            Some(("e1".parse().unwrap(), "g1".parse().unwrap()))
+        );
+        assert!(castle.legal_move(&start).is_none());
+
+        let name = |mv: Option<LegalMove<White>>| mv.map(|mv| mv.data().to_string());
+        assert_eq!(name(book.best_move(&start)), Some("e2e4".to_string()));
+        assert_eq!(
+            name(book.weighted_move(&start, 29)),
+            Some("e2e4".to_string())
+        );
+        assert_eq!(
+            name(book.weighted_move(&start, 30)),
+            Some("d2d4".to_string())
+        );
+        assert_eq!(
+            name(book.weighted_move(&start, 40)),
+            Some("e2e4".to_string())
+        );
+        let black = start.apply_move(&start.parse_move("e2e4").unwrap());
+        assert!(book.best_move::<Black>(&black).is_none());
+        assert!(Book::from_bytes(&bytes[1..], book.keys()).is_err());
+
+        let e4 = start.parse_move("e2e4").unwrap().data();
+        let entry = BookEntry::new(key, e4, 30);
+        assert_eq!(entry, book.entries(&start)[0]);
+        let written = Book::new(vec![entry], book.keys()).to_bytes();
+        assert_eq!(written, self::book(&[(key, raw_move("e2", "e4"), 30)]));
+    }
+}
@@ -0,0 +1,761 @@
+//! Endgame tablebases of a lone king against up to two pieces, generated by
+//! retrograde analysis.
+//!
+//! A table stores one byte per placement of its pieces: 0 for draws and
+//! impossible placements, otherwise the distance to mate in plies plus one. The
+//! strong side is stored as White, positions where Black is the strong side are
+//! probed with the board mirrored. Placements are reduced by symmetry, the strong
+//! king is kept in the triangle a1-d1-d4, or on files a to d when there are pawns.
+//!
+//! Generation marks the mates found with [`Board::outcome`], then walks back from
+//! them through un-moves: a position of the strong side is won when one of its
+//! moves reaches a lost position, a position of the lone king is lost once all of
+//! its moves reach won positions. Captures of the lone king are looked up in the
+//! tables of the smaller material, generated along.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::piece::team::Side;
+use crate::search::{Probe, Tablebase, Wdl};
+use crate::{Black, Board, Outcome, Piece, PieceType, Pos, Position, Team, White};
+
+/// Most pieces of the strong side besides its king.
+pub const MAX_PIECES: usize = 2;
+/// Extension of table files.
+pub const EXTENSION: &str = "egt";
+const MAGIC: &[u8; 8] = b"CHESSEGT";
+/// Squares of the strong king without pawns, the triangle a1-d1-d4.
+const TRIANGLE: [usize; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+/// Generation state of a position that cannot occur.
+const INVALID: u8 = u8::MAX;
+/// Generation state of a lone king position with a capture reaching a draw.
+const CANNOT_LOSE: u8 = u8::MAX - 1;
+
+/// Pieces of the strong side besides its king, e.g. "KBNK" for bishop and knight.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Material {
+    pieces: Vec<PieceType>,
+}
+
+impl Material {
+    #[must_use]
+    pub fn pieces(&self) -> &[PieceType] {
+        &self.pieces
+    }
+
+    fn has_pawns(&self) -> bool {
+        self.pieces.contains(&PieceType::Pawn)
+    }
+
+    fn without(&self, i: usize) -> Self {
+        let mut pieces = self.pieces.clone();
+        pieces.remove(i);
+        Self { pieces }
+    }
+}
+
+/// Order of the pieces in material names and placements, queens first.
+const fn order(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::King => 0,
+        PieceType::Queen => 1,
+        PieceType::Rook => 2,
+        PieceType::Bishop => 3,
+        PieceType::Knight => 4,
+        PieceType::Pawn => 5,
+    }
+}
+
+impl FromStr for Material {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pieces = s
+            .strip_prefix('K')
+            .and_then(|pieces| pieces.strip_suffix('K'))
+            .ok_or_else(|| format!("Material '{s}' must start and end with a king"))?;
+        let mut pieces = pieces
+            .chars()
+            .map(|c| {
+                PieceType::from_char(c)
+                    .filter(|&piece_type| piece_type != PieceType::King)
+                    .ok_or_else(|| format!("Unknown piece '{c}' in material '{s}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !(1..=MAX_PIECES).contains(&pieces.len()) {
+            return Err(format!(
+                "Material '{s}' must have 1 to {MAX_PIECES} pieces besides the kings"
+            ));
+        }
+        pieces.sort_by_key(|&piece_type| order(piece_type));
+        Ok(Self { pieces })
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pieces: String = self.pieces.iter().map(|p| p.to_char()).collect();
+        write!(f, "K{pieces}K")
+    }
+}
+
+/// Placement of the pieces of a material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Placement {
+    strong_to_move: bool,
+    /// Square indices of the strong king, the lone king and the pieces in the
+    /// order of the material, unused ones 0.
+    squares: [usize; 2 + MAX_PIECES],
+}
+
+/// Symmetry-reduced index of the placements of a material.
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+    len: usize,
+    pawns: bool,
+}
+
+impl Layout {
+    fn new(material: &Material) -> Self {
+        Self {
+            len: 2 + material.pieces.len(),
+            pawns: material.has_pawns(),
+        }
+    }
+
+    const fn kings(self) -> usize {
+        if self.pawns { 32 } else { TRIANGLE.len() }
+    }
+
+    fn size(self) -> usize {
+        2 * self.kings() * 64_usize.pow(u32::try_from(self.len - 1).unwrap_or(0))
+    }
+
+    fn king_index(self, square: usize) -> Option<usize> {
+        if self.pawns {
+            (square % 8 < 4).then_some(square / 8 * 4 + square % 8)
+        } else {
+            TRIANGLE.iter().position(|&s| s == square)
+        }
+    }
+
+    const fn king_square(self, index: usize) -> usize {
+        if self.pawns {
+            index / 4 * 8 + index % 4
+        } else {
+            TRIANGLE[index]
+        }
+    }
+
+    /// Index of `placement`, the smallest of its symmetric images. Pawns only
+    /// allow mirroring the files.
+    fn index(self, placement: &Placement) -> usize {
+        let transforms = if self.pawns { 2 } else { 8 };
+        (0..transforms)
+            .filter_map(|transform| {
+                let square = |i: usize| mirror(placement.squares[i], transform);
+                let king = self.king_index(square(0))?;
+                Some((1..self.len).fold(
+                    usize::from(!placement.strong_to_move) * self.kings() + king,
+                    |index, i| index * 64 + square(i),
+                ))
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn placement(self, mut index: usize) -> Placement {
+        let mut squares = [0; 2 + MAX_PIECES];
+        for i in (1..self.len).rev() {
+            squares[i] = index % 64;
+            index /= 64;
+        }
+        squares[0] = self.king_square(index % self.kings());
+        Placement {
+            strong_to_move: index / self.kings() == 0,
+            squares,
+        }
+    }
+}
+
+/// Image of `square` mirrored on the files, the ranks and the diagonal a1-h8 as
+/// selected by the bits of `transform`.
+const fn mirror(square: usize, transform: u8) -> usize {
+    let (mut rank, mut file) = (square / 8, square % 8);
+    if transform & 1 != 0 {
+        file = 7 - file;
+    }
+    if transform & 2 != 0 {
+        rank = 7 - rank;
+    }
+    if transform & 4 != 0 {
+        (rank, file) = (file, rank);
+    }
+    rank * 8 + file
+}
+
+/// Endgame table of one material, see the [module documentation](self).
+#[derive(Clone, PartialEq, Eq)]
+pub struct EndgameTable {
+    material: Material,
+    data: Vec<u8>,
+}
+
+impl fmt::Debug for EndgameTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndgameTable")
+            .field("material", &self.material.to_string())
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+impl EndgameTable {
+    /// Generates the table of `material` and, along, those of its captures.
+    #[must_use]
+    pub fn generate(material: &Material) -> Self {
+        let captures = (0..material.pieces.len())
+            .map(|i| {
+                let smaller = material.without(i);
+                (!smaller.pieces.is_empty()).then(|| Self::generate(&smaller))
+            })
+            .collect();
+        Generator::new(material, captures).run()
+    }
+
+    #[must_use]
+    pub const fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Longest distance to mate in plies, `None` when nothing is won.
+    #[must_use]
+    pub fn longest_mate(&self) -> Option<u32> {
+        self.data
+            .iter()
+            .max()
+            .filter(|&&value| value > 0)
+            .map(|&value| u32::from(value) - 1)
+    }
+
+    /// Result of `position`, `None` when its material is not that of the table.
+    #[must_use]
+    pub fn probe(&self, position: &Position) -> Option<Probe> {
+        let placement = match position {
+            Position::White(board) => self.placement(board),
+            Position::Black(board) => self.placement(board),
+        }?;
+        let value = self.data[Layout::new(&self.material).index(&placement)];
+        if value == 0 {
+            return Some(Probe {
+                wdl: Wdl::Draw,
+                distance: None,
+            });
+        }
+        Some(Probe {
+            wdl: if placement.strong_to_move {
+                Wdl::Win
+            } else {
+                Wdl::Loss
+            },
+            distance: Some(u32::from(value) - 1),
+        })
+    }
+
+    /// Placement of `board` with the strong side as White.
+    fn placement<S: Side + Clone>(&self, board: &Board<S>) -> Option<Placement> {
+        let pieces: Vec<_> = Pos::all()
+            .filter_map(|pos| board.at(pos).map(|piece| (piece, pos)))
+            .collect();
+        let strong = pieces
+            .iter()
+            .find(|(piece, _)| piece.piece_type() != PieceType::King)?
+            .0
+            .team();
+        // Black as the strong side plays up the board once the ranks are mirrored
+        let square = |pos: Pos| match strong {
+            Team::White => pos.index(),
+            Team::Black => pos.index() ^ 0o70,
+        };
+        let mut own: Vec<_> = pieces
+            .iter()
+            .filter(|(piece, _)| piece.team() == strong && piece.piece_type() != PieceType::King)
+            .map(|&(piece, pos)| (piece.piece_type(), square(pos)))
+            .collect();
+        own.sort_by_key(|&(piece_type, _)| order(piece_type));
+        let types: Vec<_> = own.iter().map(|&(piece_type, _)| piece_type).collect();
+        let king = |team: Team| {
+            pieces
+                .iter()
+                .find(|(piece, _)| piece.team() == team && piece.piece_type() == PieceType::King)
+                .map(|&(_, pos)| square(pos))
+        };
+        if types != self.material.pieces || pieces.len() != 2 + own.len() {
+            return None;
+        }
+        let mut squares = [0; 2 + MAX_PIECES];
+        squares[0] = king(strong)?;
+        squares[1] = king(strong.enemy())?;
+        for (i, &(_, square)) in own.iter().enumerate() {
+            squares[2 + i] = square;
+        }
+        Some(Placement {
+            strong_to_move: S::TEAM == strong,
+            squares,
+        })
+    }
+
+    /// Bytes of the table as a file: a magic number, the material and the data.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.material.to_string();
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(u8::try_from(name.len()).unwrap_or_default());
+        bytes.extend(name.bytes());
+        bytes.extend(&self.data);
+        bytes
+    }
+
+    /// # Errors
+    ///
+    /// When `bytes` are not a table file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let rest = bytes
+            .strip_prefix(MAGIC)
+            .ok_or("Not an endgame table, wrong magic number")?;
+        let (&len, rest) = rest.split_first().ok_or("Endgame table without material")?;
+        let (name, data) = rest
+            .split_at_checked(usize::from(len))
+            .ok_or("Endgame table without material")?;
+        let material: Material = std::str::from_utf8(name)
+            .map_err(|_| "Endgame table material is not text".to_string())?
+            .parse()?;
+        let size = Layout::new(&material).size();
+        if data.len() != size {
+            return Err(format!(
+                "Endgame table {material} must have {size} entries, found {}",
+                data.len()
+            ));
+        }
+        Ok(Self {
+            material,
+            data: data.to_vec(),
+        })
+    }
+
+    /// # Errors
+    ///
+    /// When the file cannot be read or is not a table.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+/// Retrograde analysis of one material.
+struct Generator {
+    material: Material,
+    layout: Layout,
+    /// Tables of the material left after the lone king captures each piece,
+    /// `None` for a bare king.
+    captures: Vec<Option<EndgameTable>>,
+    data: Vec<u8>,
+    /// Per position [`INVALID`], [`CANNOT_LOSE`], 0 for positions of the strong
+    /// side, or for the lone king the number of moves not yet known to lose.
+    state: Vec<u8>,
+    /// Lone king positions by distance of a capture reaching a won position.
+    captures_won: Vec<Vec<usize>>,
+}
+
+impl Generator {
+    fn new(material: &Material, captures: Vec<Option<EndgameTable>>) -> Self {
+        let layout = Layout::new(material);
+        Self {
+            material: material.clone(),
+            layout,
+            captures,
+            data: vec![0; layout.size()],
+            state: vec![INVALID; layout.size()],
+            captures_won: vec![Vec::new(); usize::from(u8::MAX) + 1],
+        }
+    }
+
+    fn run(mut self) -> EndgameTable {
+        let mut level = Vec::new();
+        for index in 0..self.layout.size() {
+            let placement = self.layout.placement(index);
+            if !self.possible(&placement) || self.layout.index(&placement) != index {
+                continue;
+            }
+            let pieces = self.pieces(&placement);
+            if placement.strong_to_move {
+                let board = Board::<White>::with_pieces(pieces);
+                if !in_check(&board, placement.squares[1], Team::White) {
+                    self.state[index] = 0;
+                }
+            } else {
+                let board = Board::<Black>::with_pieces(pieces);
+                if !in_check(&board, placement.squares[0], Team::Black) {
+                    self.state[index] = self.lone_king_moves(&board, placement, index);
+                    if self.data[index] == 1 {
+                        level.push(index);
+                    }
+                }
+            }
+        }
+
+        let mut distance = 0;
+        while !level.is_empty() || self.captures_won[distance..].iter().any(|l| !l.is_empty()) {
+            let Ok(value) = u8::try_from(distance + 2) else {
+                break;
+            };
+            let mut next = Vec::new();
+            if distance % 2 == 0 {
+                for &index in &level {
+                    for parent in self.parents(index) {
+                        if self.state[parent] == 0 && self.data[parent] == 0 {
+                            self.data[parent] = value;
+                            next.push(parent);
+                        }
+                    }
+                }
+            } else {
+                let mut losing = std::mem::take(&mut self.captures_won[distance]);
+                for &index in &level {
+                    let mut parents = self.parents(index);
+                    // each distinct child takes away one move of its parent
+                    parents.sort_unstable();
+                    parents.dedup();
+                    losing.extend(parents);
+                }
+                for parent in losing {
+                    let state = &mut self.state[parent];
+                    if (1..CANNOT_LOSE).contains(state) {
+                        *state -= 1;
+                        if *state == 0 && self.data[parent] == 0 {
+                            self.data[parent] = value;
+                            next.push(parent);
+                        }
+                    }
+                }
+            }
+            level = next;
+            distance += 1;
+        }
+        EndgameTable {
+            material: self.material,
+            data: self.data,
+        }
+    }
+
+    /// Whether the pieces are on distinct squares and no pawn stands on the first rank.
+    fn possible(&self, placement: &Placement) -> bool {
+        let squares = &placement.squares[..self.layout.len];
+        squares
+            .iter()
+            .enumerate()
+            .all(|(i, square)| !squares[..i].contains(square))
+            && self
+                .material
+                .pieces
+                .iter()
+                .zip(&squares[2..])
+                .all(|(&piece_type, &square)| piece_type != PieceType::Pawn || square >= 8)
+    }
+
+    fn pieces(&self, placement: &Placement) -> Vec<(Piece, Pos)> {
+        let kinds = [
+            Piece::new(PieceType::King, Team::White),
+            Piece::new(PieceType::King, Team::Black),
+        ]
+        .into_iter()
+        .chain(
+            self.material
+                .pieces
+                .iter()
+                .map(|&piece_type| Piece::new(piece_type, Team::White)),
+        );
+        kinds
+            .zip(placement.squares)
+            .filter_map(|(piece, square)| Some((piece, Pos::from_index(square)?)))
+            .collect()
+    }
+
+    /// Counts the moves of the lone king on move in `board`: its captures and the
+    /// distinct positions its other moves reach. Marks mates and returns
+    /// [`CANNOT_LOSE`] when a capture reaches a draw.
+    fn lone_king_moves(&mut self, board: &Board<Black>, placement: Placement, index: usize) -> u8 {
+        let moves = board.team_legal_moves();
+        if moves.is_empty() {
+            if let Some(Outcome::Checkmate { .. }) = board.outcome() {
+                self.data[index] = 1;
+            }
+            return 0;
+        }
+        let mut children = Vec::new();
+        let mut captures = 0;
+        for mv in &moves {
+            let to = mv.data().to().pos().index();
+            let mut child = placement;
+            child.strong_to_move = true;
+            child.squares[1] = to;
+            let Some(taken) = placement.squares[2..self.layout.len]
+                .iter()
+                .position(|&square| square == to)
+            else {
+                children.push(self.layout.index(&child));
+                continue;
+            };
+            let Some(table) = &self.captures[taken] else {
+                return CANNOT_LOSE;
+            };
+            child.squares[2 + taken..].rotate_left(1);
+            let value = table.data[Layout::new(&table.material).index(&child)];
+            if value == 0 {
+                return CANNOT_LOSE;
+            }
+            self.captures_won[usize::from(value) - 1].push(index);
+            captures += 1;
+        }
+        children.sort_unstable();
+        children.dedup();
+        u8::try_from(children.len() + captures).unwrap_or(CANNOT_LOSE)
+    }
+
+    /// Indices of the positions with a move reaching the position `index`, the
+    /// move played by the side not on move there. Positions that cannot occur are
+    /// included, the callers skip them by their state.
+    fn parents(&self, index: usize) -> Vec<usize> {
+        let placement = self.layout.placement(index);
+        let squares = &placement.squares[..self.layout.len];
+        let empty = |square: usize| !squares.contains(&square);
+        let mut parents = Vec::new();
+        let movers: Vec<(usize, PieceType)> = if placement.strong_to_move {
+            vec![(1, PieceType::King)]
+        } else {
+            strong_pieces(&self.material).collect()
+        };
+        for (slot, piece_type) in movers {
+            let from = squares[slot];
+            let mut origins = Vec::new();
+            if piece_type == PieceType::Pawn {
+                // a white pawn came from below, two squares from the second rank
+                if from >= 16 && empty(from - 8) {
+                    origins.push(from - 8);
+                    if from / 8 == 3 && empty(from - 16) {
+                        origins.push(from - 16);
+                    }
+                }
+            } else if let Some(pos) = Pos::from_index(from) {
+                let slides = matches!(
+                    piece_type,
+                    PieceType::Queen | PieceType::Rook | PieceType::Bishop
+                );
+                let piece = Piece::new(piece_type, Team::White);
+                origins.extend(
+                    piece
+                        .raw_moves(pos)
+                        .map(|raw_move| raw_move.to.index())
+                        .filter(|&to| empty(to) && (!slides || clear_between(squares, from, to))),
+                );
+            }
+            for origin in origins {
+                let mut parent = placement;
+                parent.strong_to_move = !placement.strong_to_move;
+                parent.squares[slot] = origin;
+                parents.push(self.layout.index(&parent));
+            }
+        }
+        parents
+    }
+}
+
+/// Slots of the strong side's pieces in a placement with their kinds.
+fn strong_pieces(material: &Material) -> impl Iterator<Item = (usize, PieceType)> + '_ {
+    std::iter::once((0, PieceType::King)).chain(
+        material
+            .pieces
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, p)| (2 + i, p)),
+    )
+}
+
+/// Whether no square strictly between `from` and `to`, on a line or a diagonal,
+/// is occupied.
+fn clear_between(squares: &[usize], from: usize, to: usize) -> bool {
+    let step = |a: usize, b: usize| b.cmp(&a) as isize;
+    let (rank_step, file_step) = (step(from / 8, to / 8), step(from % 8, to % 8));
+    let distance = (from / 8).abs_diff(to / 8).max((from % 8).abs_diff(to % 8));
+    (1..distance).all(|i| {
+        let i = i.cast_signed();
+        let rank = (from / 8).cast_signed() + rank_step * i;
+        let file = (from % 8).cast_signed() + file_step * i;
+        !squares.contains(&(rank * 8 + file).cast_unsigned())
+    })
+}
+
+/// Whether the king on `square` is attacked by `by`.
+fn in_check<S: Side + Clone>(board: &Board<S>, square: usize, by: Team) -> bool {
+    Pos::from_index(square).is_some_and(|pos| board.is_attacked(pos, by))
+}
+
+/// Endgame tables probed together, e.g. by the search.
+#[derive(Clone, Debug, Default)]
+pub struct Endgames {
+    tables: Vec<EndgameTable>,
+}
+
+impl Endgames {
+    #[must_use]
+    pub const fn new(tables: Vec<EndgameTable>) -> Self {
+        Self { tables }
+    }
+
+    /// Reads all table files of the directory `dir`.
+    ///
+    /// # Errors
+    ///
+    /// When the directory or one of its tables cannot be read.
+    pub fn open_dir(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+        let mut tables = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| format!("{}: {err}", dir.display()))?
+                .path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                tables.push(EndgameTable::open(path)?);
+            }
+        }
+        Ok(Self::new(tables))
+    }
+
+    #[must_use]
+    pub fn tables(&self) -> &[EndgameTable] {
+        &self.tables
+    }
+}
+
+impl Tablebase for Endgames {
+    fn max_pieces(&self) -> usize {
+        self.tables
+            .iter()
+            .map(|table| 2 + table.material.pieces.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn probe(&self, position: &Position) -> Option<Probe> {
+        self.tables.iter().find_map(|table| table.probe(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(table: &EndgameTable, fen: &str) -> Option<Probe> {
+        fen.parse().ok().and_then(|position| table.probe(&position))
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn king_and_queen_against_king() {
+        let material: Material = "KQK".parse().unwrap();
+        let table = EndgameTable::generate(&material);
+        // mate in 10 moves at most, 20 plies with the lone king on move
+        assert_eq!(table.longest_mate(), Some(20));
+        let won = |distance| {
+            Some(Probe {
+                wdl: Wdl::Win,
+                distance: Some(distance),
+            })
+        };
+        assert_eq!(probe(&table, "k7/8/1K6/8/8/8/7Q/8 w"), won(1));
+        // the same mirrored and with Black as the strong side
+        assert_eq!(probe(&table, "8/7Q/8/8/8/1K6/8/k7 w"), won(1));
+        assert_eq!(probe(&table, "8/7q/8/8/8/1k6/8/K7 b"), won(1));
+        assert_eq!(
+            probe(&table, "k7/3Q4/1K6/8/8/8/8/8 b"),
+            Some(Probe {
+                wdl: Wdl::Loss,
+                distance: Some(2),
+            })
+        );
+        // the lone king takes the queen
+        assert_eq!(
+            probe(&table, "8/8/8/8/8/8/1kQ5/7K b").map(|probe| probe.wdl),
+            Some(Wdl::Draw)
+        );
+        assert_eq!(probe(&table, "k7/8/1K6/8/8/8/7R/8 w"), None);
+
+        let bytes = table.to_bytes();
+        assert_eq!(EndgameTable::from_bytes(&bytes), Ok(table));
+        assert!(EndgameTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert_eq!(material.to_string(), "KQK");
+        assert_eq!(
+            "KNBK".parse::<Material>().map(|m| m.to_string()),
+            Ok("KBNK".to_string())
+        );
+        assert!("KQRBK".parse::<Material>().is_err());
+        assert!("KK".parse::<Material>().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn king_and_rook_against_king() {
+        let table = EndgameTable::generate(&"KRK".parse().unwrap());
+        // mate in 16 moves at most
+        assert_eq!(table.longest_mate(), Some(32));
+        assert_eq!(
+            probe(&table, "k7/8/1K6/8/8/8/8/7R w"),
+            Some(Probe {
+                wdl: Wdl::Win,
+                distance: Some(1),
+            })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn king_and_pawn_against_king_is_a_draw() {
+        // without promotion the pawn ends stuck on the last rank
+        let table = EndgameTable::generate(&"KPK".parse().unwrap());
+        assert_eq!(table.longest_mate(), None);
+        assert_eq!(
+            probe(&table, "8/8/8/8/8/8/3P4/k1K5 w").map(|probe| probe.wdl),
+            Some(Wdl::Draw)
+        );
+    }
+
+    #[test]
+    #[ignore = "generates for minutes in debug builds, run with --release -- --ignored"]
+    #[allow(clippy::unwrap_used)]
+    fn king_bishop_and_knight_against_king() {
+        let table = EndgameTable::generate(&"KBNK".parse().unwrap());
+        // mate in 33 moves at most
+        assert_eq!(table.longest_mate(), Some(66));
+        assert_eq!(
+            probe(&table, "7k/4N3/3B2K1/8/8/8/8/8 w"),
+            Some(Probe {
+                wdl: Wdl::Win,
+                distance: Some(1),
+            })
+        );
+        // the lone king takes the knight, bishop and king cannot mate
+        assert_eq!(
+            probe(&table, "8/8/8/8/8/7K/1kN5/7B b").map(|probe| probe.wdl),
+            Some(Wdl::Draw)
+        );
+    }
+}
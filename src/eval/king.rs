@@ -0,0 +1,125 @@
+//! King safety terms.
+
+use crate::eval::{Term, sign};
+use crate::piece::team::Side;
+use crate::{Board, PieceType, Pos, Team};
+
+/// Own pawn one and two ranks in front of the king.
+const SHIELD: [Term; 2] = [Term::new(15, 0), Term::new(8, 0)];
+/// Enemy pawn one, two and three ranks in front of the king.
+const STORM: [Term; 3] = [Term::new(-5, 0), Term::new(-20, 0), Term::new(-10, 0)];
+/// File next to the king without own pawns, and without any pawns.
+pub(super) const SEMI_OPEN_FILE: Term = Term::new(-15, 0);
+const OPEN_FILE: Term = Term::new(-25, 0);
+/// Cap of the king zone attack penalty in the middlegame.
+const MAX_ATTACK_PENALTY: i32 = 500;
+
+/// King safety of one position, every term from white's view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KingSafety {
+    pub shield: Term,
+    pub storm: Term,
+    pub files: Term,
+    pub attacks: Term,
+}
+
+impl KingSafety {
+    pub fn new<S: Side + Clone>(board: &Board<S>) -> Self {
+        let mut safety = Self::default();
+        for team in [Team::White, Team::Black] {
+            let Some(king) = Pos::all().find(|&pos| {
+                board
+                    .at(pos)
+                    .is_some_and(|p| p.team() == team && p.piece_type() == PieceType::King)
+            }) else {
+                continue;
+            };
+            let sign = sign(team);
+            for file in king.file().saturating_sub(1)..=(king.file() + 1).min(7) {
+                safety.shield += shield(board, king, file, team).scaled(sign);
+                safety.storm += storm(board, king, file, team).scaled(sign);
+                safety.files += open_file(board, file, team).scaled(sign);
+            }
+            safety.attacks += zone_attacks(board, king, team).scaled(sign);
+        }
+        safety
+    }
+}
+
+fn pawn_at<S: Side + Clone>(board: &Board<S>, pos: Option<Pos>, team: Team) -> bool {
+    pos.and_then(|pos| board.at(pos))
+        .is_some_and(|p| p.team() == team && p.piece_type() == PieceType::Pawn)
+}
+
+fn file_pos(king: Pos, file: usize, ranks_ahead: isize, team: Team) -> Option<Pos> {
+    let rank = isize::try_from(king.rank()).ok()? + ranks_ahead * team.direction();
+    Pos::new(rank, isize::try_from(file).ok()?)
+}
+
+fn shield<S: Side + Clone>(board: &Board<S>, king: Pos, file: usize, team: Team) -> Term {
+    (1..=2)
+        .zip(SHIELD)
+        .find(|&(ahead, _)| pawn_at(board, file_pos(king, file, ahead, team), team))
+        .map_or_else(Term::default, |(_, bonus)| bonus)
+}
+
+fn storm<S: Side + Clone>(board: &Board<S>, king: Pos, file: usize, team: Team) -> Term {
+    (1..=3)
+        .zip(STORM)
+        .filter(|&(ahead, _)| pawn_at(board, file_pos(king, file, ahead, team), team.enemy()))
+        .fold(Term::default(), |sum, (_, penalty)| sum + penalty)
+}
+
+fn open_file<S: Side + Clone>(board: &Board<S>, file: usize, team: Team) -> Term {
+    let mut own = false;
+    let mut enemy = false;
+    for rank in 0..8 {
+        if let Some(piece) = Pos::new(rank, isize::try_from(file).unwrap_or(0))
+            .and_then(|pos| board.at(pos))
+            .filter(|p| p.piece_type() == PieceType::Pawn)
+        {
+            own |= piece.team() == team;
+            enemy |= piece.team() != team;
+        }
+    }
+    match (own, enemy) {
+        (true, _) => Term::default(),
+        (false, true) => SEMI_OPEN_FILE,
+        (false, false) => OPEN_FILE,
+    }
+}
+
+const fn attack_weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Knight | PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 5,
+        PieceType::King | PieceType::Pawn => 0,
+    }
+}
+
+/// Penalty for enemy pieces attacking the king square and the squares around it,
+/// growing with the square of the weighted attacks once two pieces take part.
+fn zone_attacks<S: Side + Clone>(board: &Board<S>, king: Pos, team: Team) -> Term {
+    let mut attackers = 0_u64;
+    let mut units = 0;
+    for rank in -1..=1 {
+        for file in -1..=1 {
+            let Some(pos) = king.checked_add((rank, file)) else {
+                continue;
+            };
+            for sq in board.attackers_of(pos, team.enemy()) {
+                let weight = sq.content().map_or(0, |p| attack_weight(p.piece_type()));
+                if weight > 0 {
+                    attackers |= 1 << sq.pos().index();
+                    units += weight;
+                }
+            }
+        }
+    }
+    if attackers.count_ones() < 2 {
+        return Term::default();
+    }
+    let penalty = (units * units).min(MAX_ATTACK_PENALTY);
+    Term::new(-penalty, -penalty / 4)
+}
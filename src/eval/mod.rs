@@ -0,0 +1,301 @@
+//! Static evaluation of positions.
+
+mod king;
+mod pawns;
+mod psqt;
+
+use king::KingSafety;
+use pawns::{PawnEntry, PawnTable};
+
+use std::ops::{Add, AddAssign, Neg, Sub};
+
+use crate::piece::team::Side;
+use crate::{Board, PieceType, Pos, Square, Team};
+
+/// Game phase of the starting material, the phase falls to 0 as pieces come off.
+pub const MAX_PHASE: i32 = 24;
+
+const TEMPO: Term = Term::new(20, 10);
+/// Entries of the pawn hash table of a new [`Evaluator`].
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// Value of a term in the middlegame and in the endgame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Term {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Term {
+    #[must_use]
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Blends the middlegame and endgame values by game phase.
+    #[must_use]
+    pub const fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+
+    const fn scaled(self, n: i32) -> Self {
+        Self::new(self.mg * n, self.eg * n)
+    }
+}
+
+impl Add for Term {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl AddAssign for Term {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Term {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for Term {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+/// Breakdown of an evaluation by term, every term from white's view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvalTrace {
+    pub material: Term,
+    pub piece_squares: Term,
+    pub mobility: Term,
+    pub doubled_pawns: Term,
+    pub isolated_pawns: Term,
+    pub backward_pawns: Term,
+    /// Passed pawns by rank, with a bonus for a free path to promotion.
+    pub passed_pawns: Term,
+    /// Own pawns in front of the king.
+    pub king_shield: Term,
+    /// Enemy pawns advancing on the king.
+    pub king_storm: Term,
+    /// Open and semi-open files next to the king.
+    pub king_files: Term,
+    /// Weighted enemy attacks on the squares around the king.
+    pub king_attacks: Term,
+    pub tempo: Term,
+    /// Game phase from 0 in a pawn endgame to [`MAX_PHASE`] with all pieces on board.
+    pub phase: i32,
+}
+
+impl EvalTrace {
+    /// Sum of all terms from white's view.
+    #[must_use]
+    pub fn total(&self) -> Term {
+        self.material
+            + self.piece_squares
+            + self.mobility
+            + self.doubled_pawns
+            + self.isolated_pawns
+            + self.backward_pawns
+            + self.passed_pawns
+            + self.king_shield
+            + self.king_storm
+            + self.king_files
+            + self.king_attacks
+            + self.tempo
+    }
+
+    /// Tapered evaluation in centipawns from the view of `team`.
+    #[must_use]
+    pub fn score(&self, team: Team) -> i32 {
+        let score = self.total().taper(self.phase);
+        match team {
+            Team::White => score,
+            Team::Black => -score,
+        }
+    }
+}
+
+/// Evaluator caching pawn structure in a pawn hash table.
+#[derive(Clone, Debug)]
+pub struct Evaluator {
+    pawns: PawnTable,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Evaluator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            pawns: PawnTable::new(PAWN_TABLE_SIZE),
+        }
+    }
+
+    /// Evaluation of `board` in centipawns from the view of the side on move.
+    pub fn evaluate<S: Side + Clone>(&mut self, board: &Board<S>) -> i32 {
+        self.trace(board).score(S::TEAM)
+    }
+
+    /// Evaluation of `board` broken down by term.
+    pub fn trace<S: Side + Clone>(&mut self, board: &Board<S>) -> EvalTrace {
+        let pawns = self.pawns.get(board);
+        trace_with(board, &pawns)
+    }
+
+    pub fn clear(&mut self) {
+        self.pawns.clear();
+    }
+}
+
+/// Evaluation of `board` in centipawns from the view of the side on move.
+#[must_use]
+pub fn evaluate<S: Side + Clone>(board: &Board<S>) -> i32 {
+    trace(board).score(S::TEAM)
+}
+
+/// Evaluation of `board` broken down by term.
+#[must_use]
+pub fn trace<S: Side + Clone>(board: &Board<S>) -> EvalTrace {
+    trace_with(board, &PawnEntry::new(board))
+}
+
+fn trace_with<S: Side + Clone>(board: &Board<S>, pawns: &PawnEntry) -> EvalTrace {
+    let king = KingSafety::new(board);
+    let mut trace = EvalTrace {
+        king_shield: king.shield,
+        king_storm: king.storm,
+        king_files: king.files,
+        king_attacks: king.attacks,
+        doubled_pawns: pawns.doubled,
+        isolated_pawns: pawns.isolated,
+        backward_pawns: pawns.backward,
+        passed_pawns: pawns.passed + pawns.free_passers(board),
+        ..EvalTrace::default()
+    };
+    for pos in Pos::all() {
+        let Some(piece) = board.at(pos) else {
+            continue;
+        };
+        let sign = sign(piece.team());
+        let piece_type = piece.piece_type();
+
+        if piece_type != PieceType::King {
+            let value = piece_type.value();
+            trace.material += Term::new(value, value).scaled(sign);
+        }
+        let (mg, eg) = psqt::value(piece_type, piece.team(), pos);
+        trace.piece_squares += Term::new(mg, eg).scaled(sign);
+        let moves = i32::try_from(board.mobility(Square::new(pos, board))).unwrap_or(0);
+        trace.mobility += mobility_weight(piece_type).scaled(moves * sign);
+        trace.phase += phase_weight(piece_type);
+    }
+    trace.phase = trace.phase.min(MAX_PHASE);
+    trace.tempo = TEMPO.scaled(sign(S::TEAM));
+    trace
+}
+
+const fn sign(team: Team) -> i32 {
+    match team {
+        Team::White => 1,
+        Team::Black => -1,
+    }
+}
+
+const fn mobility_weight(piece_type: PieceType) -> Term {
+    match piece_type {
+        PieceType::Knight => Term::new(4, 4),
+        PieceType::Bishop => Term::new(5, 5),
+        PieceType::Rook => Term::new(2, 4),
+        PieceType::Queen => Term::new(1, 2),
+        PieceType::King | PieceType::Pawn => Term::new(0, 0),
+    }
+}
+
+const fn phase_weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Knight | PieceType::Bishop => 1,
+        PieceType::Rook => 2,
+        PieceType::Queen => 4,
+        PieceType::King | PieceType::Pawn => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::White;
+
+    #[test]
+    fn default_board_is_balanced() {
+        let trace = trace(&Board::default());
+        assert_eq!(trace.phase, MAX_PHASE);
+        assert_eq!(trace.material, Term::default());
+        assert_eq!(trace.piece_squares, Term::default());
+        assert_eq!(trace.mobility, Term::default());
+        assert_eq!(evaluate(&Board::default()), TEMPO.mg);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn pawn_structure() {
+        // white: doubled c-pawns, only isolated pawns, free passed pawn on h6
+        // black: backward pawn on d7 facing the white pawn on e5
+        let board = Board::<White>::from_str(concat!(
+            "  ,  ,  ,  ,wK,  ,  ,  \n",
+            "  ,  ,wP,  ,  ,  ,  ,  \n",
+            "  ,  ,wP,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,wP,  ,  ,  \n",
+            "  ,  ,  ,  ,bP,  ,  ,wP\n",
+            "  ,  ,  ,bP,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,bK,  ,  ,  "
+        ))
+        .unwrap();
+        let trace = Evaluator::new().trace(&board);
+        assert_eq!(trace, super::trace(&board));
+        assert_eq!(trace.doubled_pawns, pawns::DOUBLED);
+        assert_eq!(trace.isolated_pawns, pawns::ISOLATED.scaled(4));
+        assert_eq!(trace.backward_pawns, -pawns::BACKWARD);
+        assert!(trace.passed_pawns.eg > 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn king_safety() {
+        // white king sheltered on g1, black king on g8 lacks the g-pawn and
+        // faces the queen on the g-file and the bishop on d5
+        let board = Board::<White>::from_str(concat!(
+            "  ,  ,  ,  ,  ,  ,wK,  \n",
+            "  ,  ,  ,  ,  ,wP,wP,wP\n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,wB,  ,  ,wQ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,bP,  ,bP\n",
+            "  ,  ,  ,  ,  ,  ,bK,  "
+        ))
+        .unwrap();
+        let trace = trace(&board);
+        assert_eq!(trace.king_shield.mg, 15);
+        assert_eq!(trace.king_files, king::SEMI_OPEN_FILE.scaled(-1));
+        assert!(trace.king_attacks.mg > 0);
+        assert_eq!(trace.king_storm, Term::default());
+    }
+}
@@ -0,0 +1,173 @@
+//! Pawn structure terms and the pawn hash table caching them.
+
+use crate::eval::Term;
+use crate::piece::team::Side;
+use crate::{Board, PieceType, Pos, Team};
+
+pub(super) const DOUBLED: Term = Term::new(-10, -20);
+pub(super) const ISOLATED: Term = Term::new(-10, -15);
+pub(super) const BACKWARD: Term = Term::new(-8, -10);
+/// Passed pawn bonus by rank counted from the pawn's own side.
+const PASSED_MG: [i32; 8] = [0, 5, 10, 15, 25, 45, 80, 0];
+const PASSED_EG: [i32; 8] = [0, 10, 15, 25, 45, 80, 130, 0];
+/// Extra bonus of a passed pawn with no piece in front of it.
+const FREE_PASSED_MG: [i32; 8] = [0, 0, 2, 5, 10, 20, 35, 0];
+const FREE_PASSED_EG: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+
+/// Pawn structure of a position, every term from white's view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PawnEntry {
+    key: u64,
+    pub doubled: Term,
+    pub isolated: Term,
+    pub backward: Term,
+    pub passed: Term,
+    /// Passed pawns of each team as bitsets of square indices.
+    passers: [u64; 2],
+}
+
+impl PawnEntry {
+    pub fn new<S: Side + Clone>(board: &Board<S>) -> Self {
+        let mut pawns = [0; 2];
+        for pos in Pos::all() {
+            if let Some(piece) = board.at(pos)
+                && piece.piece_type() == PieceType::Pawn
+            {
+                pawns[piece.team().index()] |= 1 << pos.index();
+            }
+        }
+
+        let mut entry = Self {
+            key: board.pawn_key(),
+            ..Self::default()
+        };
+        for team in [Team::White, Team::Black] {
+            let own = pawns[team.index()];
+            let enemy = pawns[team.enemy().index()];
+            let sign = super::sign(team);
+
+            for file in 0..8 {
+                let count = (own & (FILE_A << file)).count_ones();
+                if count > 1 {
+                    entry.doubled += DOUBLED.scaled((count.cast_signed() - 1) * sign);
+                }
+            }
+
+            for pos in Pos::all().filter(|pos| own & 1 << pos.index() != 0) {
+                let file = pos.file();
+                let relative_rank = relative_rank(pos, team);
+                if own & adjacent_files(file) == 0 {
+                    entry.isolated += ISOLATED.scaled(sign);
+                } else if own & adjacent_files(file) & behind(pos, team) == 0
+                    && pos
+                        .checked_add((team.direction(), 0))
+                        .is_some_and(|stop| attacked_by_pawns(stop, team.enemy(), enemy))
+                {
+                    entry.backward += BACKWARD.scaled(sign);
+                }
+                let front = (FILE_A << file | adjacent_files(file)) & !behind(pos, team);
+                if enemy & front == 0 {
+                    entry.passed +=
+                        Term::new(PASSED_MG[relative_rank], PASSED_EG[relative_rank]).scaled(sign);
+                    entry.passers[team.index()] |= 1 << pos.index();
+                }
+            }
+        }
+        entry
+    }
+
+    /// Bonus of passed pawns whose path to promotion is empty, it depends on all
+    /// pieces so it is not kept in the pawn hash table.
+    pub fn free_passers<S: Side + Clone>(&self, board: &Board<S>) -> Term {
+        let mut bonus = Term::default();
+        for team in [Team::White, Team::Black] {
+            for pos in Pos::all().filter(|pos| self.passers[team.index()] & 1 << pos.index() != 0) {
+                let mut path = pos.checked_add((team.direction(), 0));
+                let mut free = true;
+                while let Some(square) = path {
+                    if board.at(square).is_some() {
+                        free = false;
+                        break;
+                    }
+                    path = square.checked_add((team.direction(), 0));
+                }
+                if free {
+                    let rank = relative_rank(pos, team);
+                    bonus += Term::new(FREE_PASSED_MG[rank], FREE_PASSED_EG[rank])
+                        .scaled(super::sign(team));
+                }
+            }
+        }
+        bonus
+    }
+}
+
+/// Fixed-size cache of pawn structure evaluations keyed by the pawn key.
+#[derive(Clone, Debug)]
+pub struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnTable {
+    /// Creates a table holding up to `size` entries.
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: vec![None; size.max(1)],
+        }
+    }
+
+    /// Pawn structure of `board`, evaluated only when it is not cached yet.
+    pub fn get<S: Side + Clone>(&mut self, board: &Board<S>) -> PawnEntry {
+        let key = board.pawn_key();
+        let slot = usize::try_from(key % self.entries.len() as u64).unwrap_or(0);
+        match self.entries[slot] {
+            Some(entry) if entry.key == key => entry,
+            _ => {
+                let entry = PawnEntry::new(board);
+                self.entries[slot] = Some(entry);
+                entry
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+const fn relative_rank(pos: Pos, team: Team) -> usize {
+    match team {
+        Team::White => pos.rank(),
+        Team::Black => 7 - pos.rank(),
+    }
+}
+
+const fn adjacent_files(file: usize) -> u64 {
+    let mut files = 0;
+    if file > 0 {
+        files |= FILE_A << (file - 1);
+    }
+    if file < 7 {
+        files |= FILE_A << (file + 1);
+    }
+    files
+}
+
+/// Squares on the rank of `pos` and on the ranks behind it from the view of `team`.
+const fn behind(pos: Pos, team: Team) -> u64 {
+    match team {
+        Team::White if pos.rank() == 7 => u64::MAX,
+        Team::White => (1 << (8 * (pos.rank() + 1))) - 1,
+        Team::Black => u64::MAX << (8 * pos.rank()),
+    }
+}
+
+fn attacked_by_pawns(pos: Pos, team: Team, pawns: u64) -> bool {
+    [1, -1].into_iter().any(|side| {
+        pos.checked_add((-team.direction(), side))
+            .is_some_and(|from| pawns & 1 << from.index() != 0)
+    })
+}
@@ -5,15 +5,27 @@
     clippy::expect_used
 )]
 
+mod attack;
 mod board;
+pub mod book;
+pub mod endgame;
+pub mod eval;
 mod movement;
+pub mod pgn;
 mod piece;
+mod position;
+pub mod problem;
+pub mod search;
 mod square;
+mod zobrist;
 
-pub use board::Board;
-pub use movement::{LegalMove, RawMove};
+pub use attack::AttackMap;
+pub use board::{Board, Outcome, START_FEN};
+pub use movement::{LegalMove, MAX_MOVES, Move, MoveList, RawMove};
 pub use piece::{
+    Piece,
     piece_type::PieceType,
     team::{Black, Side, Team, White},
 };
+pub use position::Position;
 pub use square::{Pos, Square};
@@ -1,6 +1,11 @@
+use std::env;
+use std::hash::{BuildHasher, Hasher, RandomState};
 use std::io;
 
-use chess::{Black, Board, LegalMove, PieceType, Pos, RawMove, Side, Square, White};
+use chess::book::{Book, PolyglotKeys};
+use chess::endgame::Endgames;
+use chess::search::{Tablebase, Wdl};
+use chess::{Black, Board, LegalMove, PieceType, Pos, Position, RawMove, Side, Square, White};
 
 enum Game {
     White(Board<White>),
@@ -13,15 +18,7 @@ enum MoveType {
 }
 
 fn parse_piece(c: char) -> Result<PieceType, String> {
-    match c {
-        'K' => Ok(PieceType::King),
-        'Q' => Ok(PieceType::Queen),
-        'R' => Ok(PieceType::Rook),
-        'N' => Ok(PieceType::Knight),
-        'B' => Ok(PieceType::Bishop),
-        'P' => Ok(PieceType::Pawn),
-        _ => Err(format!("Uknown piece '{c}', must be one of K,Q,R,N,B,P")),
-    }
+    PieceType::from_char(c).ok_or_else(|| format!("Uknown piece '{c}', must be one of K,Q,R,N,B,P"))
 }
 
 fn parse_file(c: char) -> Result<isize, String> {
@@ -87,13 +84,91 @@ fn parse_move(input: &str) -> Result<(PieceType, RawMove, MoveType), String> {
     Ok((piece, mv, mv_type))
 }
 
-fn user_move<S: Side + Clone>(board: &Board<S>) -> Result<LegalMove<S>, String> {
+/// Value of the command line option `name`, e.g. `--book`.
+fn option(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Opening book given with `--book FILE`.
+fn book_from_args() -> Result<Option<Book>, String> {
+    option("--book")
+        .map(|book| Book::open(book, PolyglotKeys::default()))
+        .transpose()
+}
+
+/// Endgame tables of the directory given with `--tables DIR`.
+fn tables_from_args() -> Result<Option<Endgames>, String> {
+    option("--tables").map(Endgames::open_dir).transpose()
+}
+
+fn move_notation<S: Side + Clone>(mv: &LegalMove<S>) -> String {
+    let data = mv.data();
+    let piece = data
+        .from()
+        .content()
+        .map_or('?', |p| p.piece_type().to_char());
+    let kind = if data.is_capture() { 'x' } else { '-' };
+    format!("{piece}{}{kind}{}", data.from().pos(), data.to().pos())
+}
+
+fn print_book_moves<S: Side + Clone>(book: Option<&Book>, board: &Board<S>) {
+    let Some(moves) = book.map(|book| book.moves(board)) else {
+        return;
+    };
+    if !moves.is_empty() {
+        let moves: Vec<_> = moves
+            .iter()
+            .map(|(mv, weight)| format!("{} ({weight})", move_notation(mv)))
+            .collect();
+        println!("Book moves: {}, enter 'book' to play one", moves.join(", "));
+    }
+}
+
+fn print_endgame<S: Side + Clone>(tables: Option<&Endgames>, board: &Board<S>) {
+    let Some(probe) = tables.and_then(|tables| tables.probe(&Position::from(board))) else {
+        return;
+    };
+    match (probe.wdl, probe.distance) {
+        (Wdl::Win, Some(plies)) => println!(
+            "Endgame table: {} mates in {} moves",
+            S::TEAM,
+            plies.div_ceil(2)
+        ),
+        (Wdl::Loss, Some(plies)) => println!(
+            "Endgame table: {} gets mated in {} moves",
+            S::TEAM,
+            plies / 2
+        ),
+        (Wdl::Win, None) => println!("Endgame table: {} wins", S::TEAM),
+        (Wdl::Loss, None) => println!("Endgame table: {} loses", S::TEAM),
+        (Wdl::Draw, _) => println!("Endgame table: draw"),
+    }
+}
+
+fn user_move<S: Side + Clone>(
+    board: &Board<S>,
+    book: Option<&Book>,
+    tables: Option<&Endgames>,
+) -> Result<LegalMove<S>, String> {
+    print_book_moves(book, board);
+    print_endgame(tables, board);
     println!("{} on move: ", S::TEAM);
 
     let mut mv = String::new();
     io::stdin().read_line(&mut mv).expect("Failed to read line");
     println!("Playing {}", mv);
 
+    if mv.trim() == "book" {
+        let random = RandomState::new().build_hasher().finish();
+        return book
+            .and_then(|book| book.weighted_move(board, random))
+            .ok_or_else(|| "No book move in this position".to_string());
+    }
+
     let (piece, mv, mv_type) = parse_move(mv.trim())?;
 
     let from_piece = board
@@ -136,6 +211,13 @@ fn user_move<S: Side + Clone>(board: &Board<S>) -> Result<LegalMove<S>, String>
 }
 
 fn main() {
+    let (book, tables) = match (book_from_args(), tables_from_args()) {
+        (Ok(book), Ok(tables)) => (book, tables),
+        (Err(e), _) | (_, Err(e)) => {
+            println!("[ERROR] {e}");
+            return;
+        }
+    };
     let mut game = Game::White(Board::default());
     match game {
         Game::White(ref board) => println!("{board}"),
@@ -151,7 +233,7 @@ fn main() {
                     println!("{:?} has no legal moves, GG!", board.team());
                     break;
                 }
-                let user_move = user_move(&board);
+                let user_move = user_move(&board, book.as_ref(), tables.as_ref());
                 match user_move {
                     Ok(legal_move) => {
                         let board = board.apply_move(&legal_move);
@@ -171,7 +253,7 @@ fn main() {
                     println!("{:?} has no legal moves, GG!", board.team());
                     break;
                 }
-                let user_move = user_move(&board);
+                let user_move = user_move(&board, book.as_ref(), tables.as_ref());
                 match user_move {
                     Ok(legal_move) => {
                         let board = board.apply_move(&legal_move);
@@ -1,7 +1,12 @@
+use std::fmt;
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
 use crate::{Pos, piece::team::Side, square::Square};
 
+/// Upper bound on the number of legal moves in any chess position.
+pub const MAX_MOVES: usize = 218;
+
 #[derive(Clone, Copy, Debug)]
 pub struct RawMove {
     pub from: Pos,
@@ -23,16 +28,29 @@ pub enum Move {
 }
 
 impl Move {
+    #[must_use]
     pub const fn from(&self) -> Square {
         match self {
             Self::Simple { from, to: _ } | Self::Capture { from, to: _ } => *from,
         }
     }
+    #[must_use]
     pub const fn to(&self) -> Square {
         match self {
             Self::Simple { from: _, to } | Self::Capture { from: _, to } => *to,
         }
     }
+    #[must_use]
+    pub const fn is_capture(&self) -> bool {
+        matches!(self, Self::Capture { .. })
+    }
+}
+
+/// Coordinate notation of the move as used by UCI, e.g. "e2e4".
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from().pos(), self.to().pos())
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -40,12 +58,20 @@ pub struct PossibleMove {
     move_data: Move,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Debug)]
 pub struct LegalMove<S: Side> {
     move_data: Move,
     _side: PhantomData<S>,
 }
 
+impl<S: Side> Clone for LegalMove<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Side> Copy for LegalMove<S> {}
+
 impl PossibleMove {
     pub(crate) const fn new(move_data: Move) -> Self {
         Self { move_data }
@@ -69,3 +95,118 @@ impl<S: Side> LegalMove<S> {
         self.move_data
     }
 }
+
+/// Fixed-capacity list of legal moves kept on the stack.
+///
+/// Boards are only read when no side can have more than [`MAX_MOVES`] moves,
+/// pushing past the capacity is a bug and panics.
+#[derive(Debug)]
+pub struct MoveList<S: Side> {
+    moves: [LegalMove<S>; MAX_MOVES],
+    len: usize,
+}
+
+impl<S: Side> Clone for MoveList<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Side> Copy for MoveList<S> {}
+
+impl<S: Side> MoveList<S> {
+    #[must_use]
+    pub const fn new() -> Self {
+        const PLACEHOLDER: Move = Move::Simple {
+            from: Square::empty(Pos::A1),
+            to: Square::empty(Pos::A1),
+        };
+        Self {
+            moves: [LegalMove::new(PLACEHOLDER); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub(crate) const fn push(&mut self, mv: LegalMove<S>) {
+        assert!(self.len < MAX_MOVES, "more moves than MAX_MOVES");
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    /// Orders the moves by starting square a1, b1, ..., h8, then by destination
+    /// square in the same order.
+    pub fn sort_by_squares(&mut self) {
+        self.sort_unstable_by_key(square_order);
+    }
+
+    /// Moves captures before quiet moves, keeping square order within each group.
+    pub fn sort_captures_first(&mut self) {
+        self.sort_unstable_by_key(|mv| (!mv.data().is_capture(), square_order(mv)));
+    }
+
+    /// Orders captures by most valuable victim, then least valuable attacker,
+    /// followed by quiet moves in square order.
+    pub fn sort_mvv_lva(&mut self) {
+        self.sort_unstable_by_key(|mv| {
+            let value = |sq: Square| sq.content().map_or(0, |piece| piece.piece_type().value());
+            let attacker = if mv.data().is_capture() {
+                value(mv.data().from())
+            } else {
+                0
+            };
+            (-value(mv.data().to()), attacker, square_order(mv))
+        });
+    }
+}
+
+const fn square_order<S: Side>(mv: &LegalMove<S>) -> (usize, usize) {
+    (mv.data().from().pos().index(), mv.data().to().pos().index())
+}
+
+impl<S: Side> Default for MoveList<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Side> FromIterator<LegalMove<S>> for MoveList<S> {
+    fn from_iter<I: IntoIterator<Item = LegalMove<S>>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for mv in iter {
+            list.push(mv);
+        }
+        list
+    }
+}
+
+impl<S: Side> Deref for MoveList<S> {
+    type Target = [LegalMove<S>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl<S: Side> DerefMut for MoveList<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<S: Side> IntoIterator for MoveList<S> {
+    type Item = LegalMove<S>;
+    type IntoIter = std::iter::Take<std::array::IntoIter<LegalMove<S>, MAX_MOVES>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter().take(self.len)
+    }
+}
+
+impl<'a, S: Side> IntoIterator for &'a MoveList<S> {
+    type Item = &'a LegalMove<S>;
+    type IntoIter = std::slice::Iter<'a, LegalMove<S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,190 @@
+//! Reader of games in Portable Game Notation.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Result of a game as written in PGN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// Game still in progress or result unknown, written "*".
+    Unknown,
+}
+
+impl FromStr for GameResult {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(Self::WhiteWins),
+            "0-1" => Ok(Self::BlackWins),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            _ => Err(format!("Unknown result '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        })
+    }
+}
+
+/// Game of a PGN file: its tag pairs and its moves in standard algebraic
+/// notation. Comments, variations and annotations are dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Game {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+}
+
+impl Game {
+    /// Value of the tag `name`, e.g. `White` or `WhiteElo`.
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Result of the `Result` tag, [`GameResult::Unknown`] when missing.
+    #[must_use]
+    pub fn result(&self) -> GameResult {
+        self.tag("Result")
+            .and_then(|result| result.parse().ok())
+            .unwrap_or(GameResult::Unknown)
+    }
+}
+
+/// Reads all games of a PGN file.
+///
+/// # Errors
+///
+/// When a tag pair is malformed.
+pub fn parse(pgn: &str) -> Result<Vec<Game>, String> {
+    let mut games = Vec::new();
+    let mut game = Game::default();
+    let mut in_moves = false;
+    let mut movetext = String::new();
+    for line in pgn.lines() {
+        let line = line.trim();
+        if line.starts_with('%') {
+            continue;
+        }
+        if line.starts_with('[') && !in_moves_of_comment(&movetext) {
+            if in_moves {
+                game.moves = parse_movetext(&movetext);
+                games.push(std::mem::take(&mut game));
+                movetext.clear();
+                in_moves = false;
+            }
+            game.tags.push(parse_tag(line)?);
+        } else if !line.is_empty() || in_moves {
+            in_moves = true;
+            movetext += line;
+            movetext.push('\n');
+        }
+    }
+    if in_moves || !game.tags.is_empty() {
+        game.moves = parse_movetext(&movetext);
+        games.push(game);
+    }
+    Ok(games)
+}
+
+/// Whether `movetext` ends inside a brace comment, where a line may start with '['.
+fn in_moves_of_comment(movetext: &str) -> bool {
+    movetext.rfind('{') > movetext.rfind('}')
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let malformed = || format!("Malformed tag '{line}'");
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(malformed)?;
+    let (name, value) = inner
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(malformed)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(malformed)?;
+    Ok((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Moves of a movetext, without move numbers, comments, variations, annotation
+/// glyphs and the result.
+fn parse_movetext(movetext: &str) -> Vec<String> {
+    let mut plain = String::new();
+    let mut variations = 0_usize;
+    let mut chars = movetext.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars.by_ref().find(|&c| c == '}');
+            }
+            ';' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '(' => variations += 1,
+            ')' => variations = variations.saturating_sub(1),
+            _ if variations > 0 => {}
+            '.' => plain.push(' '),
+            _ => plain.push(c),
+        }
+    }
+    plain
+        .split_whitespace()
+        .filter(|token| {
+            !token.starts_with('$')
+                && !token.chars().all(|c| c.is_ascii_digit())
+                && token.parse::<GameResult>().is_err()
+        })
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn games_with_comments_and_variations() {
+        let pgn = concat!(
+            "[Event \"Club \\\"Open\\\"\"]\n",
+            "[Result \"1-0\"]\n",
+            "\n",
+            "1. e4 {best by test} e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 $1 Nc6\n",
+            "; a line comment\n",
+            "3. Bb5 a6 1-0\n",
+            "\n",
+            "[Event \"Second\"]\n",
+            "\n",
+            "1. d4 d5 *\n",
+        );
+        let games = parse(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("Club \"Open\""));
+        assert_eq!(games[0].result(), GameResult::WhiteWins);
+        assert_eq!(games[0].moves, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(games[1].moves, ["d4", "d5"]);
+        assert_eq!(games[1].result(), GameResult::Unknown);
+        assert!(parse("[Event Club]").is_err());
+    }
+}
@@ -4,7 +4,7 @@ pub mod team;
 use piece_type::PieceType;
 use team::Team;
 
-use std::{fmt::Debug, str::FromStr, vec};
+use std::{fmt::Debug, str::FromStr};
 
 use crate::{Pos, movement::RawMove};
 
@@ -16,108 +16,165 @@ pub struct Piece {
     has_moved: bool,
 }
 
-fn checked_push(vec: &mut Vec<RawMove>, from: Pos, (c, r): (isize, isize)) {
-    if let Some(to) = Pos::checked_add(from, (c, r)) {
-        vec.push(RawMove { from, to });
+const KING_QUEEN_DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+const ROOK_DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+    (1, 2),
+    (1, -2),
+    (-1, 2),
+    (-1, -2),
+];
+// TODO: en passant
+// TODO: transformation on last rank
+const WHITE_PAWN_OFFSETS: [(isize, isize); 4] = [(1, 0), (1, 1), (1, -1), (2, 0)];
+const BLACK_PAWN_OFFSETS: [(isize, isize); 4] = [(-1, 0), (-1, 1), (-1, -1), (-2, 0)];
+
+/// Iterator over the destinations of a piece on an empty board, walking each
+/// direction up to `range` steps.
+struct RawMoves {
+    from: Pos,
+    directions: &'static [(isize, isize)],
+    range: isize,
+    direction: usize,
+    distance: isize,
+}
+
+impl Iterator for RawMoves {
+    type Item = RawMove;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let &(r, c) = self.directions.get(self.direction)?;
+            self.distance += 1;
+            if self.distance <= self.range
+                && let Some(to) = self
+                    .from
+                    .checked_add((r * self.distance, c * self.distance))
+            {
+                return Some(RawMove {
+                    from: self.from,
+                    to,
+                });
+            }
+            self.direction += 1;
+            self.distance = 0;
+        }
     }
 }
 
 impl Piece {
-    pub fn raw_moves(self, from: Pos) -> Vec<RawMove> {
-        match self.piece_type {
-            PieceType::King => {
-                let mut v = vec![];
-                for i in -1..=1 {
-                    for j in -1..=1 {
-                        if i != 0 && j != 0 {
-                            checked_push(&mut v, from, (i, j));
-                        }
-                    }
-                }
-                v
-                // TODO: castling
-            }
-            PieceType::Queen => {
-                let mut v = vec![];
-                for i in 1..=7 {
-                    // diagonals
-                    checked_push(&mut v, from, (i, i));
-                    checked_push(&mut v, from, (i, -i));
-                    checked_push(&mut v, from, (-i, i));
-                    checked_push(&mut v, from, (-i, -i));
-                    // horizontal and vertical
-                    checked_push(&mut v, from, (0, i));
-                    checked_push(&mut v, from, (0, -i));
-                    checked_push(&mut v, from, (i, 0));
-                    checked_push(&mut v, from, (-i, 0));
-                }
-                v
-            }
-            PieceType::Rook => {
-                let mut v = vec![];
-                for i in 1..=7 {
-                    checked_push(&mut v, from, (0, i));
-                    checked_push(&mut v, from, (0, -i));
-                    checked_push(&mut v, from, (i, 0));
-                    checked_push(&mut v, from, (-i, 0));
-                }
-                v
-            }
-            PieceType::Knight => {
-                let offsets = vec![
-                    (2, 1),
-                    (2, -1),
-                    (-2, 1),
-                    (-2, -1),
-                    (1, 2),
-                    (1, -2),
-                    (-1, 2),
-                    (-1, -2),
-                ];
-
-                let mut v = vec![];
-                for (i, j) in offsets {
-                    checked_push(&mut v, from, (i, j));
-                }
-                v
-            }
-            PieceType::Bishop => {
-                let mut v = vec![];
-                for i in 1..=7 {
-                    checked_push(&mut v, from, (i, i));
-                    checked_push(&mut v, from, (i, -i));
-                    checked_push(&mut v, from, (-i, i));
-                    checked_push(&mut v, from, (-i, -i));
-                }
-                v
-            }
+    #[must_use]
+    pub const fn new(piece_type: PieceType, team: Team) -> Self {
+        Self {
+            piece_type,
+            team,
+            has_moved: false,
+        }
+    }
+
+    /// Parses a FEN piece letter, uppercase for white and lowercase for black.
+    #[must_use]
+    pub const fn from_fen_char(c: char) -> Option<Self> {
+        let team = if c.is_ascii_uppercase() {
+            Team::White
+        } else {
+            Team::Black
+        };
+        match PieceType::from_char(c.to_ascii_uppercase()) {
+            Some(piece_type) => Some(Self::new(piece_type, team)),
+            None => None,
+        }
+    }
+
+    /// FEN piece letter, uppercase for white and lowercase for black.
+    #[must_use]
+    pub const fn fen_char(self) -> char {
+        let c = self.piece_type.to_char();
+        match self.team {
+            Team::White => c,
+            Team::Black => c.to_ascii_lowercase(),
+        }
+    }
+
+    /// Unicode chess symbol, e.g. '♔' for the white king and '♚' for the black king.
+    #[must_use]
+    pub const fn unicode_char(self) -> char {
+        match (self.team, self.piece_type) {
+            (Team::White, PieceType::King) => '♔',
+            (Team::White, PieceType::Queen) => '♕',
+            (Team::White, PieceType::Rook) => '♖',
+            (Team::White, PieceType::Bishop) => '♗',
+            (Team::White, PieceType::Knight) => '♘',
+            (Team::White, PieceType::Pawn) => '♙',
+            (Team::Black, PieceType::King) => '♚',
+            (Team::Black, PieceType::Queen) => '♛',
+            (Team::Black, PieceType::Rook) => '♜',
+            (Team::Black, PieceType::Bishop) => '♝',
+            (Team::Black, PieceType::Knight) => '♞',
+            (Team::Black, PieceType::Pawn) => '♟',
+        }
+    }
+
+    pub fn raw_moves(self, from: Pos) -> impl Iterator<Item = RawMove> {
+        // TODO: castling
+        let (directions, range): (&'static [(isize, isize)], isize) = match self.piece_type {
+            PieceType::King => (&KING_QUEEN_DIRECTIONS, 1),
+            PieceType::Queen => (&KING_QUEEN_DIRECTIONS, 7),
+            PieceType::Rook => (&ROOK_DIRECTIONS, 7),
+            PieceType::Bishop => (&BISHOP_DIRECTIONS, 7),
+            PieceType::Knight => (&KNIGHT_OFFSETS, 1),
             PieceType::Pawn => {
-                // TODO: en passant
-                // TODO: transformation on last rank
-                let mut v = vec![];
-                checked_push(&mut v, from, (self.team.direction(), 0));
-                checked_push(&mut v, from, (self.team.direction(), 1));
-                checked_push(&mut v, from, (self.team.direction(), -1));
-                if !self.has_moved {
-                    checked_push(&mut v, from, (2 * self.team.direction(), 0));
+                let offsets = match self.team {
+                    Team::White => &WHITE_PAWN_OFFSETS,
+                    Team::Black => &BLACK_PAWN_OFFSETS,
+                };
+                // the double step is last and only allowed for an unmoved pawn
+                if self.has_moved {
+                    (&offsets[..3], 1)
+                } else {
+                    (&offsets[..], 1)
                 }
-                v
             }
+        };
+        RawMoves {
+            from,
+            directions,
+            range,
+            direction: 0,
+            distance: 0,
         }
     }
 
+    #[must_use]
     pub const fn team(self) -> Team {
         self.team
     }
 
+    #[must_use]
     pub const fn piece_type(self) -> PieceType {
         self.piece_type
     }
 
+    #[must_use]
     pub const fn has_moved(self) -> bool {
         self.has_moved
     }
 
+    #[must_use]
     pub const fn touch_piece(self) -> Self {
         Self {
             piece_type: self.piece_type,
@@ -138,23 +195,12 @@ impl FromStr for Piece {
             c => return Err(format!("Unknown Team {c}")),
         };
         let piece_type = s.next().ok_or("Missing piece type when parsing Piece")?;
-        let piece_type = match piece_type {
-            'K' => PieceType::King,
-            'Q' => PieceType::Queen,
-            'R' => PieceType::Rook,
-            'N' => PieceType::Knight,
-            'B' => PieceType::Bishop,
-            'P' => PieceType::Pawn,
-            c => return Err(format!("Uknown Piece type {c}")),
-        };
+        let piece_type = PieceType::from_char(piece_type)
+            .ok_or_else(|| format!("Uknown Piece type {piece_type}"))?;
         if s.next().is_some() {
             return Err("Too many characters for Piece".to_string());
         }
-        Ok(Self {
-            piece_type,
-            team,
-            has_moved: false,
-        })
+        Ok(Self::new(piece_type, team))
     }
 }
 
@@ -164,15 +210,45 @@ impl std::fmt::Display for Piece {
             Team::White => 'w',
             Team::Black => 'b',
         };
-        let piece_type = match self.piece_type {
-            PieceType::King => 'K',
-            PieceType::Queen => 'Q',
-            PieceType::Rook => 'R',
-            PieceType::Knight => 'N',
-            PieceType::Bishop => 'B',
-            PieceType::Pawn => 'P',
-        };
+        let piece_type = self.piece_type.to_char();
         let str = format!("{team}{piece_type}");
         f.write_str(&str)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fen_char_round_trip() {
+        for c in "KQRBNPkqrbnp".chars() {
+            assert_eq!(Piece::from_fen_char(c).map(Piece::fen_char), Some(c));
+        }
+        assert_eq!(Piece::from_fen_char('x'), None);
+        assert_eq!(
+            Piece::from_fen_char('k'),
+            Some(Piece::new(PieceType::King, Team::Black))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn king_steps_in_all_directions() {
+        // the king once only stepped diagonally
+        let king = Piece::new(PieceType::King, Team::White);
+        let mut to: Vec<String> = king
+            .raw_moves("e4".parse().unwrap())
+            .map(|mv| mv.to.to_string())
+            .collect();
+        to.sort();
+        assert_eq!(to, ["d3", "d4", "d5", "e3", "e5", "f3", "f4", "f5"]);
+        assert_eq!(king.raw_moves("a1".parse().unwrap()).count(), 3);
+    }
+
+    #[test]
+    fn unicode_kings() {
+        assert_eq!(Piece::new(PieceType::King, Team::White).unicode_char(), '♔');
+        assert_eq!(Piece::new(PieceType::King, Team::Black).unicode_char(), '♚');
+    }
+}
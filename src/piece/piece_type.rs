@@ -10,6 +10,59 @@ pub enum PieceType {
     Pawn,
 }
 
+impl PieceType {
+    /// Parses the uppercase letter used in algebraic and FEN notation.
+    #[must_use]
+    pub const fn from_char(c: char) -> Option<Self> {
+        match c {
+            'K' => Some(Self::King),
+            'Q' => Some(Self::Queen),
+            'R' => Some(Self::Rook),
+            'N' => Some(Self::Knight),
+            'B' => Some(Self::Bishop),
+            'P' => Some(Self::Pawn),
+            _ => None,
+        }
+    }
+
+    /// Uppercase letter used in algebraic and FEN notation.
+    #[must_use]
+    pub const fn to_char(self) -> char {
+        match self {
+            Self::King => 'K',
+            Self::Queen => 'Q',
+            Self::Rook => 'R',
+            Self::Knight => 'N',
+            Self::Bishop => 'B',
+            Self::Pawn => 'P',
+        }
+    }
+
+    pub(crate) const fn index(self) -> usize {
+        match self {
+            Self::King => 0,
+            Self::Queen => 1,
+            Self::Rook => 2,
+            Self::Knight => 3,
+            Self::Bishop => 4,
+            Self::Pawn => 5,
+        }
+    }
+
+    /// Material value in centipawns.
+    #[must_use]
+    pub const fn value(self) -> i32 {
+        match self {
+            Self::King => 20_000,
+            Self::Queen => 900,
+            Self::Rook => 500,
+            Self::Knight => 320,
+            Self::Bishop => 330,
+            Self::Pawn => 100,
+        }
+    }
+}
+
 impl fmt::Display for PieceType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -5,7 +5,9 @@ pub struct White;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Black;
 
-pub trait Side {
+/// Side to move of a [`Board`](crate::Board), boards of either side can be shared
+/// between search threads.
+pub trait Side: Send + Sync {
     const TEAM: Team;
     type Other: Side + Clone;
 }
@@ -35,6 +37,13 @@ impl Team {
         }
     }
 
+    pub(crate) const fn index(self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
+
     #[must_use]
     pub const fn enemy(self) -> Self {
         match self {
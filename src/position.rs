@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use crate::{Black, Board, Move, Outcome, Team, White};
+
+/// Board of either side, for when the side on move is only known at runtime,
+/// e.g. after reading a FEN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Position {
+    White(Board<White>),
+    Black(Board<Black>),
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::White(Board::default())
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub const fn team(&self) -> Team {
+        match self {
+            Self::White(_) => Team::White,
+            Self::Black(_) => Team::Black,
+        }
+    }
+
+    #[must_use]
+    pub fn fen(&self) -> String {
+        match self {
+            Self::White(board) => board.fen(),
+            Self::Black(board) => board.fen(),
+        }
+    }
+
+    /// Zobrist key of the position, see [`Board::hash_key`].
+    #[must_use]
+    pub fn hash_key(&self) -> u64 {
+        match self {
+            Self::White(board) => board.hash_key(),
+            Self::Black(board) => board.hash_key(),
+        }
+    }
+
+    /// Result of the game when the side on move cannot move, see [`Board::outcome`].
+    #[must_use]
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self {
+            Self::White(board) => board.outcome(),
+            Self::Black(board) => board.outcome(),
+        }
+    }
+
+    /// Position after the legal move given in coordinate notation, e.g. "e2e4".
+    ///
+    /// # Errors
+    ///
+    /// When no legal move has that notation.
+    pub fn play(&self, mv: &str) -> Result<Self, String> {
+        Ok(match self {
+            Self::White(board) => Self::Black(board.apply_move(&board.parse_move(mv)?)),
+            Self::Black(board) => Self::White(board.apply_move(&board.parse_move(mv)?)),
+        })
+    }
+
+    /// Position after the legal move given in standard algebraic notation, e.g.
+    /// "Nf3", and that move.
+    ///
+    /// # Errors
+    ///
+    /// When the move cannot be read, see [`Board::parse_san`].
+    pub fn play_san(&self, san: &str) -> Result<(Self, Move), String> {
+        Ok(match self {
+            Self::White(board) => {
+                let mv = board.parse_san(san)?;
+                (Self::Black(board.apply_move(&mv)), mv.data())
+            }
+            Self::Black(board) => {
+                let mv = board.parse_san(san)?;
+                (Self::White(board.apply_move(&mv)), mv.data())
+            }
+        })
+    }
+}
+
+/// Reads a FEN, see [`Board::from_fen`].
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        match fen.split_whitespace().nth(1) {
+            None | Some("w") => Ok(Self::White(Board::from_fen(fen)?)),
+            Some("b") => Ok(Self::Black(Board::from_fen(fen)?)),
+            Some(side) => Err(format!("Unknown side '{side}' on move, must be 'w' or 'b'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::START_FEN;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn play_moves_from_fen() {
+        let position = Position::from_str(START_FEN).unwrap();
+        assert_eq!(position, Position::default());
+        let position = position.play("e2e4").unwrap().play("e7e5").unwrap();
+        assert_eq!(position.team(), Team::White);
+        assert_eq!(
+            position.fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(Position::from_str(&position.fen()), Ok(position.clone()));
+        assert!(position.play("e4e5").is_err());
+        assert!(Position::from_str("8/8/8/8/8/8/8/8 x").is_err());
+
+        let (position, mv) = position.play_san("Nf3").unwrap();
+        assert_eq!(mv.to_string(), "g1f3");
+        let (position, _) = position.play_san("Nc6").unwrap();
+        let (position, _) = position.play_san("Bb5").unwrap();
+        let (position, _) = position.play_san("Nge7").unwrap();
+        assert_eq!(position.play_san("Nc3").unwrap().1.to_string(), "b1c3");
+        assert_eq!(position.play_san("Bxc6!?").unwrap().1.to_string(), "b5c6");
+        assert!(position.play_san("N2d4").is_err());
+        assert!(position.play_san("O-O").is_err());
+        assert!(position.play_san("e8=Q").is_err());
+        assert!(position.play_san("e").is_err());
+
+        let rooks = Position::from_str("4k3/8/8/8/8/8/8/R4RK1 w").unwrap();
+        assert!(rooks.play_san("Rd1").is_err());
+        assert_eq!(rooks.play_san("Rad1").unwrap().1.to_string(), "a1d1");
+    }
+}
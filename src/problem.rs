@@ -0,0 +1,302 @@
+//! Solver of chess problems.
+//!
+//! Unlike the engine search, the solver proves its results: a key move of a
+//! mate in N is kept only when every defence loses within N moves. It reports
+//! all solutions, so more than one key shows a cook, and the duals of the
+//! attacking side's second move.
+//!
+//! The side on move attacks in mates and selfmates. In a helpmate the side on
+//! move starts and helps the other side mate it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::piece::team::Side;
+use crate::{Board, LegalMove, Move, Outcome};
+
+/// Goal of a problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stipulation {
+    /// The side on move mates against any defence, written "#".
+    Mate,
+    /// Both sides cooperate so that the side on move gets mated, written "h#".
+    Helpmate,
+    /// The side on move forces the other side to mate it, written "s#".
+    Selfmate,
+}
+
+/// Stipulation and number of moves of a problem, e.g. "#2" or "h#3".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub stipulation: Stipulation,
+    pub moves: u32,
+}
+
+impl FromStr for Problem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (stipulation, moves) = if let Some(moves) = s.strip_prefix("h#") {
+            (Stipulation::Helpmate, moves)
+        } else if let Some(moves) = s.strip_prefix("s#") {
+            (Stipulation::Selfmate, moves)
+        } else if let Some(moves) = s.strip_prefix('#') {
+            (Stipulation::Mate, moves)
+        } else {
+            return Err(format!(
+                "Unknown stipulation '{s}', expected e.g. #2, h#2 or s#2"
+            ));
+        };
+        match moves.parse() {
+            Ok(moves) if moves > 0 => Ok(Self { stipulation, moves }),
+            _ => Err(format!("Invalid number of moves in '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.stipulation {
+            Stipulation::Mate => "",
+            Stipulation::Helpmate => "h",
+            Stipulation::Selfmate => "s",
+        };
+        write!(f, "{prefix}#{}", self.moves)
+    }
+}
+
+/// Defence after a key answered by more than one continuation fulfilling the
+/// stipulation.
+#[derive(Clone, Debug)]
+pub struct Dual {
+    pub defence: Move,
+    pub continuations: Vec<Move>,
+}
+
+/// Solution of a problem.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// Moves of the solution: the key alone in mates and selfmates, all moves of
+    /// both sides in helpmates.
+    pub line: Vec<Move>,
+    /// Fewest moves the solution needs, less than the stipulation for a short
+    /// solution.
+    pub moves: u32,
+    /// Duals after the key, always empty in helpmates.
+    pub duals: Vec<Dual>,
+}
+
+/// Every solution of `problem` in the position of `board`. More than one
+/// solution is a cook.
+#[must_use]
+pub fn solve<S: Side + Clone>(board: &Board<S>, problem: Problem) -> Vec<Solution> {
+    let mut solver = Solver {
+        stipulation: problem.stipulation,
+        known: HashMap::new(),
+    };
+    if problem.stipulation == Stipulation::Helpmate {
+        let mut lines = Vec::new();
+        solver.help(board, 2 * problem.moves, &mut Vec::new(), &mut lines);
+        return lines
+            .into_iter()
+            .map(|line| Solution {
+                line,
+                moves: problem.moves,
+                duals: Vec::new(),
+            })
+            .collect();
+    }
+    let mut solutions = Vec::new();
+    for key in &board.team_legal_moves() {
+        let after = board.apply_move(key);
+        let Some(moves) = (1..=problem.moves).find(|&moves| solver.defend(&after, moves)) else {
+            continue;
+        };
+        solutions.push(Solution {
+            line: vec![key.data()],
+            moves,
+            duals: solver.duals(&after, problem.moves),
+        });
+    }
+    solutions
+}
+
+/// Exhaustive search remembering the result of every node.
+struct Solver {
+    stipulation: Stipulation,
+    /// Whether the stipulation is fulfilled from a position with some number
+    /// of moves or plies left, by position key.
+    known: HashMap<(u64, u32), bool>,
+}
+
+impl Solver {
+    /// Whether the side on move fulfills the stipulation in at most `moves`
+    /// moves against any defence.
+    fn attack<S: Side + Clone>(&mut self, board: &Board<S>, moves: u32) -> bool {
+        let key = (board.hash_key(), moves);
+        if let Some(&known) = self.known.get(&key) {
+            return known;
+        }
+        let fulfilled = board.team_legal_moves().iter().any(|mv| {
+            let after = board.apply_move(mv);
+            // only checks mate on the last move
+            let hopeless =
+                self.stipulation == Stipulation::Mate && moves == 1 && !after.is_in_check();
+            !hopeless && self.defend(&after, moves)
+        });
+        self.known.insert(key, fulfilled);
+        fulfilled
+    }
+
+    /// Whether every defence of the side on move fails against a stipulation
+    /// with `moves` moves left, counting the attacking move just played.
+    fn defend<S: Side + Clone>(&mut self, board: &Board<S>, moves: u32) -> bool {
+        let key = (board.hash_key(), moves);
+        if let Some(&known) = self.known.get(&key) {
+            return known;
+        }
+        let fulfilled = match (self.stipulation, board.outcome()) {
+            (Stipulation::Mate, Some(Outcome::Checkmate { .. })) => true,
+            (_, Some(_)) => false,
+            (_, None) => board.team_legal_moves().iter().all(|mv| {
+                let after = board.apply_move(mv);
+                self.defence_fails(&after, moves)
+            }),
+        };
+        self.known.insert(key, fulfilled);
+        fulfilled
+    }
+
+    /// Whether the attacking side, on move after a defence, still fulfills the
+    /// stipulation with `moves` moves left, counting the previous attacking move.
+    fn defence_fails<S: Side + Clone>(&mut self, board: &Board<S>, moves: u32) -> bool {
+        let mated = || board.is_in_check() && board.outcome().is_some();
+        (self.stipulation == Stipulation::Selfmate && mated())
+            || (moves > 1 && self.attack(board, moves - 1))
+    }
+
+    /// Defences after the key in `board` answered by more than one
+    /// continuation fulfilling the stipulation in at most `moves` moves.
+    fn duals<S: Side + Clone>(&mut self, board: &Board<S>, moves: u32) -> Vec<Dual> {
+        if moves == 1 {
+            return Vec::new();
+        }
+        let mut duals = Vec::new();
+        for defence in &board.team_legal_moves() {
+            let after = board.apply_move(defence);
+            if self.stipulation == Stipulation::Selfmate && after.outcome().is_some() {
+                continue;
+            }
+            let continuations: Vec<Move> = after
+                .team_legal_moves()
+                .iter()
+                .filter(|mv| self.defend(&after.apply_move(mv), moves - 1))
+                .map(LegalMove::data)
+                .collect();
+            if continuations.len() > 1 {
+                duals.push(Dual {
+                    defence: defence.data(),
+                    continuations,
+                });
+            }
+        }
+        duals
+    }
+
+    /// Collects into `lines` every line of exactly `plies` plies after `line`
+    /// ending in the mate of the side on move, returns whether there is one.
+    fn help<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        plies: u32,
+        line: &mut Vec<Move>,
+        lines: &mut Vec<Vec<Move>>,
+    ) -> bool {
+        if plies == 0 {
+            let mated = matches!(board.outcome(), Some(Outcome::Checkmate { .. }));
+            if mated {
+                lines.push(line.clone());
+            }
+            return mated;
+        }
+        let key = (board.hash_key(), plies);
+        if self.known.get(&key) == Some(&false) {
+            return false;
+        }
+        let mut found = false;
+        for mv in &board.team_legal_moves() {
+            let after = board.apply_move(mv);
+            if plies == 1 && !after.is_in_check() {
+                continue;
+            }
+            line.push(mv.data());
+            found |= self.help(&after, plies - 1, line, lines);
+            line.pop();
+        }
+        self.known.insert(key, found);
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Black, White};
+
+    fn keys(solutions: &[Solution]) -> Vec<String> {
+        solutions
+            .iter()
+            .map(|solution| {
+                let moves: Vec<String> = solution.line.iter().map(ToString::to_string).collect();
+                moves.join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mates() {
+        let solve_white = |fen, problem: &str| {
+            solve(
+                &Board::<White>::from_fen(fen).unwrap(),
+                problem.parse().unwrap(),
+            )
+        };
+        assert_eq!(keys(&solve_white("k7/8/1K6/8/8/8/8/7R w", "#1")), ["h1h8"]);
+        // cooked by the other king move
+        assert_eq!(
+            keys(&solve_white("k7/8/2K5/8/8/8/8/7R w", "#2")),
+            ["c6b6", "c6c7"]
+        );
+        let solutions = solve_white("k7/8/2K5/8/8/8/8/6RR w", "#2");
+        let king = solutions
+            .iter()
+            .find(|solution| solution.line[0].to_string() == "c6b6")
+            .unwrap();
+        assert_eq!(king.duals.len(), 1);
+        assert_eq!(king.duals[0].defence.to_string(), "a8b8");
+        assert_eq!(king.duals[0].continuations.len(), 2);
+        let short = solve_white("7k/8/5K2/8/8/8/8/6R1 w", "#2");
+        assert_eq!(keys(&short), ["f6f7"]);
+        assert_eq!(short[0].moves, 2);
+        // a defence by capturing the queen mates
+        let selfmate = solve_white("4R3/5k1K/2P5/4BrQ1/8/1p1P4/6p1/8 w", "s#1");
+        assert_eq!(keys(&selfmate), ["g5h5"]);
+        let helpmate = solve(
+            &Board::<Black>::from_fen("k7/8/1K6/8/8/8/8/7R b").unwrap(),
+            "h#1".parse().unwrap(),
+        );
+        assert_eq!(keys(&helpmate), ["a8b8 h1h8"]);
+    }
+
+    #[test]
+    fn stipulations() {
+        assert_eq!(
+            "h#3".parse::<Problem>().map(|problem| problem.to_string()),
+            Ok("h#3".to_string())
+        );
+        assert!("#0".parse::<Problem>().is_err());
+        assert!("=2".parse::<Problem>().is_err());
+    }
+}
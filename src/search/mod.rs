@@ -0,0 +1,1025 @@
+mod ordering;
+mod quiescence;
+mod tablebase;
+mod time;
+mod tt;
+
+pub use tablebase::{Probe, Tablebase, Wdl};
+pub use time::{Clock, TimeManager};
+pub use tt::{Bound, Entry, MoveKey, TranspositionTable};
+
+use std::fmt;
+use std::iter;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::eval::Evaluator;
+use crate::movement::Move;
+use crate::piece::team::Side;
+use crate::search::ordering::{Heuristics, MovePicker};
+use crate::{Board, LegalMove, PieceType, Pos};
+
+/// Deepest ply the search descends to.
+pub const MAX_PLY: i32 = 100;
+
+/// Transposition table size of a new [`Searcher`] in megabytes.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+const INFINITY: i32 = 32_001;
+const MATE: i32 = 32_000;
+const MATE_BOUND: i32 = MATE - MAX_PLY;
+/// Value of a tablebase win without a known distance to mate at the root, less
+/// a point per ply like mate scores.
+const TABLEBASE_WIN: i32 = MATE_BOUND - 1;
+/// Values from here on are tablebase wins or mates.
+const TABLEBASE_BOUND: i32 = TABLEBASE_WIN - MAX_PLY;
+/// Number of nodes between two checks of the stop flag and the clock.
+const CHECK_INTERVAL: u64 = 1024;
+/// Half width of the first aspiration window around the previous score.
+const ASPIRATION_WINDOW: i32 = 40;
+/// Margin per ply of depth for reverse futility pruning.
+const REVERSE_FUTILITY_MARGIN: i32 = 90;
+/// Margin and margin per ply of depth for futility pruning.
+const FUTILITY_MARGIN: (i32, i32) = (100, 120);
+
+/// Evaluation of a position from the view of the side on move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Mate in the given number of moves, negative when the side on move gets mated.
+    Mate(i32),
+    /// Tablebase win without a known distance to mate, reached in the given
+    /// number of plies, negative when the side on move loses.
+    Tablebase(i32),
+}
+
+/// Centipawns reported for a [`Score::Tablebase`] win reached right away, a
+/// centipawn less per ply. Above any evaluation, below mate.
+pub const TABLEBASE_CENTIPAWNS: i32 = 20_000;
+
+impl Score {
+    /// Inverse of [`Score::from_value`], up to the rounding of mate distances.
+    const fn value(self) -> i32 {
+        match self {
+            Self::Centipawns(cp) => cp,
+            Self::Mate(moves) if moves > 0 => MATE - 2 * moves + 1,
+            Self::Mate(moves) => -MATE - 2 * moves,
+            Self::Tablebase(plies) if plies > 0 => TABLEBASE_WIN - plies,
+            Self::Tablebase(plies) => -TABLEBASE_WIN - plies,
+        }
+    }
+
+    const fn from_value(value: i32) -> Self {
+        if value >= MATE_BOUND {
+            Self::Mate((MATE - value + 1) / 2)
+        } else if value <= -MATE_BOUND {
+            Self::Mate(-(MATE + value) / 2)
+        } else if value >= TABLEBASE_BOUND {
+            Self::Tablebase(TABLEBASE_WIN - value)
+        } else if value <= -TABLEBASE_BOUND {
+            Self::Tablebase(-(TABLEBASE_WIN + value))
+        } else {
+            Self::Centipawns(value)
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Centipawns(cp) => write!(f, "cp {cp}"),
+            Self::Mate(moves) => write!(f, "mate {moves}"),
+            Self::Tablebase(plies) if *plies > 0 => {
+                write!(f, "cp {}", TABLEBASE_CENTIPAWNS - plies)
+            }
+            Self::Tablebase(plies) => write!(f, "cp {}", -TABLEBASE_CENTIPAWNS - plies),
+        }
+    }
+}
+
+/// Bounds of a search, it ends at whichever limit is hit first. A search without
+/// limits runs until [`MAX_PLY`] or until it is stopped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    /// Clock to budget the search time from, see [`TimeManager`].
+    pub clock: Option<Clock>,
+}
+
+/// Selectivity techniques of the search, each can be turned off to measure what
+/// it brings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SearchConfig {
+    /// Let the opponent move twice and cut off when it still cannot reach beta.
+    /// Not done with only king and pawns left, where passing may be best.
+    pub null_move: bool,
+    /// Search quiet moves ordered late at reduced depth.
+    pub late_move_reductions: bool,
+    /// Cut off shallow nodes whose static evaluation is far above beta.
+    pub reverse_futility: bool,
+    /// Skip quiet moves of shallow nodes whose static evaluation is far below alpha.
+    pub futility: bool,
+    /// Search one ply deeper when in check.
+    pub check_extensions: bool,
+    /// Start each iteration with a narrow window around the previous score.
+    pub aspiration_windows: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            null_move: true,
+            late_move_reductions: true,
+            reverse_futility: true,
+            futility: true,
+            check_extensions: true,
+            aspiration_windows: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult<S: Side> {
+    /// `None` when the side on move has no legal moves.
+    pub best_move: Option<LegalMove<S>>,
+    pub score: Score,
+    /// Principal variation starting with the best move.
+    pub pv: Vec<Move>,
+    /// Depth of the last completed iteration.
+    pub depth: i32,
+    pub nodes: u64,
+    /// Best lines of the last completed iteration, best first, as many as set with
+    /// [`Searcher::set_multi_pv`]. Empty when no iteration completed.
+    pub lines: Vec<PvLine>,
+}
+
+/// One ranked line of a multi-PV search.
+#[derive(Clone, Debug)]
+pub struct PvLine {
+    pub score: Score,
+    /// Principal variation starting with the root move of the line.
+    pub pv: Vec<Move>,
+    pub depth: i32,
+    /// Nodes searched for this line in its last iteration.
+    pub nodes: u64,
+}
+
+/// Progress of a search, reported after every completed iteration.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchInfo<'a> {
+    pub depth: i32,
+    /// Best lines of the iteration, best first.
+    pub lines: &'a [PvLine],
+    /// Nodes searched so far by the calling thread.
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+/// Negamax alpha-beta search with iterative deepening.
+///
+/// With more than one thread the search is a Lazy SMP search: helper threads
+/// search the same position sharing the transposition table, odd ones a ply
+/// deeper, and the best move is voted on by all threads.
+#[derive(Debug)]
+pub struct Searcher {
+    stop: Arc<AtomicBool>,
+    evaluator: Evaluator,
+    tt: Arc<TranspositionTable>,
+    heuristics: Heuristics,
+    config: SearchConfig,
+    multi_pv: usize,
+    limits: SearchLimits,
+    start: Instant,
+    /// Time after which the search stops, from the time limit or the clock.
+    deadline: Option<Duration>,
+    nodes: u64,
+    stopped: bool,
+    helpers: Vec<Self>,
+    /// Stop flag of the helpers, set once the main thread is done.
+    helper_stop: Arc<AtomicBool>,
+    tablebase: Option<Arc<dyn Tablebase>>,
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Searcher {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_table(Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)))
+    }
+
+    fn with_table(tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            stop: Arc::new(AtomicBool::new(false)),
+            evaluator: Evaluator::new(),
+            tt,
+            heuristics: Heuristics::default(),
+            config: SearchConfig::default(),
+            multi_pv: 1,
+            limits: SearchLimits::default(),
+            start: Instant::now(),
+            deadline: None,
+            nodes: 0,
+            stopped: false,
+            helpers: Vec::new(),
+            helper_stop: Arc::new(AtomicBool::new(false)),
+            tablebase: None,
+        }
+    }
+
+    /// Flag cancelling a running search when set, e.g. from another thread.
+    ///
+    /// The search does not reset the flag, clear it before starting a new one.
+    #[must_use]
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// Replaces the transposition table by an empty one of about `megabytes`.
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt = Arc::new(TranspositionTable::new(megabytes));
+        for helper in &mut self.helpers {
+            helper.tt = Arc::clone(&self.tt);
+        }
+    }
+
+    pub fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+        for helper in &mut self.helpers {
+            helper.config = config;
+        }
+    }
+
+    /// Sets the number of threads searching, the calling one included.
+    pub fn set_threads(&mut self, threads: usize) {
+        let helpers = threads.max(1) - 1;
+        self.helpers.truncate(helpers);
+        while self.helpers.len() < helpers {
+            let mut helper = Self::with_table(Arc::clone(&self.tt));
+            helper.stop = Arc::clone(&self.helper_stop);
+            helper.config = self.config;
+            helper.tablebase.clone_from(&self.tablebase);
+            self.helpers.push(helper);
+        }
+    }
+
+    /// Sets the tablebase probed for positions with few pieces, `None` for none.
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<dyn Tablebase>>) {
+        for helper in &mut self.helpers {
+            helper.tablebase.clone_from(&tablebase);
+        }
+        self.tablebase = tablebase;
+    }
+
+    /// Sets the number of best root moves to report lines for, each found with
+    /// the moves of the lines before it excluded.
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    /// Forgets everything learned in previous searches, e.g. before a new game.
+    pub fn clear(&mut self) {
+        self.tt.clear();
+        self.evaluator.clear();
+        self.heuristics.clear();
+        for helper in &mut self.helpers {
+            helper.evaluator.clear();
+            helper.heuristics.clear();
+        }
+    }
+
+    /// Searches `board` deeper and deeper until a limit is hit or the search is
+    /// stopped, and returns the result of the last completed iteration.
+    pub fn search<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        limits: SearchLimits,
+    ) -> SearchResult<S> {
+        self.search_with_info(board, limits, |_| {})
+    }
+
+    /// Like [`Searcher::search`], calling `info` after every completed iteration.
+    pub fn search_with_info<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        limits: SearchLimits,
+        mut info: impl FnMut(&SearchInfo<'_>),
+    ) -> SearchResult<S> {
+        self.tt.new_search();
+        if self.helpers.is_empty() {
+            return self.iterate(board, limits, 0, &mut info);
+        }
+
+        let mut helpers = std::mem::take(&mut self.helpers);
+        self.helper_stop.store(false, Ordering::Relaxed);
+        let helper_limits = SearchLimits {
+            depth: limits.depth,
+            ..SearchLimits::default()
+        };
+        let (result, results) = thread::scope(|scope| {
+            let handles: Vec<_> = helpers
+                .iter_mut()
+                .zip(1..)
+                .map(|(helper, i)| {
+                    scope.spawn(move || helper.iterate(board, helper_limits, i % 2, &mut |_| {}))
+                })
+                .collect();
+            let result = self.iterate(board, limits, 0, &mut info);
+            self.helper_stop.store(true, Ordering::Relaxed);
+            let results: Vec<_> = handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect();
+            (result, results)
+        });
+        self.helpers = helpers;
+        if self.multi_pv == 1 {
+            vote(result, results)
+        } else {
+            let nodes = results.iter().map(|result| result.nodes).sum::<u64>();
+            SearchResult {
+                nodes: result.nodes + nodes,
+                ..result
+            }
+        }
+    }
+
+    /// Iterative deepening of one thread, starting `depth_offset` plies deeper.
+    fn iterate<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        limits: SearchLimits,
+        depth_offset: i32,
+        info: &mut dyn FnMut(&SearchInfo<'_>),
+    ) -> SearchResult<S> {
+        self.limits = limits;
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        let mut time = limits.clock.as_ref().map(TimeManager::new);
+        self.deadline = match (limits.time, time.map(|time| time.hard())) {
+            (Some(limit), Some(hard)) => Some(limit.min(hard)),
+            (limit, hard) => limit.or(hard),
+        };
+        self.heuristics.new_search();
+
+        let mut moves = board.team_legal_moves();
+        if let Some(tablebase) = &self.tablebase {
+            moves = tablebase::root_moves(tablebase.as_ref(), board, &moves);
+        }
+        moves.sort_mvv_lva();
+        let mut result = SearchResult {
+            best_move: moves.first().copied(),
+            score: Score::from_value(if moves.is_empty() {
+                terminal_value(board, 0)
+            } else {
+                0
+            }),
+            pv: moves.first().map(|mv| vec![mv.data()]).unwrap_or_default(),
+            depth: 0,
+            nodes: 0,
+            lines: Vec::new(),
+        };
+
+        let max_depth = limits.depth.unwrap_or(MAX_PLY).min(MAX_PLY);
+        let line_count = self.multi_pv.min(moves.len());
+        let mut previous = 0;
+        'deepening: for depth in (1 + depth_offset)..=max_depth {
+            if moves.is_empty() {
+                break;
+            }
+            let mut lines = Vec::with_capacity(line_count);
+            for k in 0..line_count {
+                let nodes = self.nodes;
+                let center = (k == 0).then_some(previous);
+                let Some((value, i, pv)) = self.search_line(board, &moves[k..], depth, center)
+                else {
+                    break 'deepening;
+                };
+                // search the best moves first in the next iteration
+                moves[k..=k + i].rotate_right(1);
+                if k == 0 {
+                    previous = value;
+                }
+                lines.push(PvLine {
+                    score: Score::from_value(value),
+                    pv,
+                    depth,
+                    nodes: self.nodes - nodes,
+                });
+            }
+            result.best_move = Some(moves[0]);
+            result.score = lines[0].score;
+            result.pv.clone_from(&lines[0].pv);
+            result.depth = depth;
+            result.lines = lines;
+            info(&SearchInfo {
+                depth,
+                lines: &result.lines,
+                nodes: self.nodes,
+                time: self.start.elapsed(),
+            });
+            if let Some(time) = &mut time
+                && !time.next_iteration(moves[0].data(), previous, self.start.elapsed())
+            {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Searches the root `moves` to `depth` and returns the best value with the
+    /// index of its move and its principal variation, or `None` when stopped.
+    ///
+    /// With a `center` from the previous iteration the search starts with a
+    /// narrow window around it and widens it on a fail low or high.
+    fn search_line<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        moves: &[LegalMove<S>],
+        depth: i32,
+        center: Option<i32>,
+    ) -> Option<(i32, usize, Vec<Move>)> {
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match center {
+            Some(center)
+                if self.config.aspiration_windows
+                    && depth >= 4
+                    && center.abs() < TABLEBASE_BOUND =>
+            {
+                (center - delta, center + delta)
+            }
+            _ => (-INFINITY, INFINITY),
+        };
+        loop {
+            let (value, best) = self.search_root(board, moves, depth, alpha, beta);
+            if self.stopped {
+                return None;
+            }
+            // widen the window on the failing side until the score fits
+            if value <= alpha && alpha > -INFINITY {
+                alpha = (alpha - delta).max(-INFINITY);
+            } else if value >= beta && beta < INFINITY {
+                beta = (beta + delta).min(INFINITY);
+            } else {
+                return best.map(|(i, pv)| (value, i, pv));
+            }
+            delta *= 2;
+        }
+    }
+
+    /// Searches all root moves and returns the best value with the index of the
+    /// best move and its principal variation, if any move raised alpha.
+    fn search_root<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        moves: &[LegalMove<S>],
+        depth: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> (i32, Option<(usize, Vec<Move>)>) {
+        let mut best = -INFINITY;
+        let mut best_line = None;
+        let mut child_pv = Vec::new();
+        for (i, mv) in moves.iter().enumerate() {
+            let value = -self.negamax(
+                &board.apply_move(mv),
+                depth - 1,
+                1,
+                -beta,
+                -alpha,
+                Some(mv.data()),
+                &mut child_pv,
+            );
+            if self.stopped {
+                break;
+            }
+            if value > best {
+                best = value;
+                if value > alpha {
+                    alpha = value;
+                    let mut pv = vec![mv.data()];
+                    pv.extend_from_slice(&child_pv);
+                    best_line = Some((i, pv));
+                    if value >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+        (best, best_line)
+    }
+
+    /// Alpha-beta search of `board` at `ply` from the root. `prev` is the move
+    /// leading to the node, `None` after a null move.
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+    fn negamax<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        mut depth: i32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        prev: Option<Move>,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        let in_check = board.is_in_check();
+        if in_check && self.config.check_extensions {
+            depth += 1;
+        }
+        if depth <= 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        let moves = board.team_legal_moves();
+        if moves.is_empty() {
+            return terminal_value(board, ply);
+        }
+        if ply >= MAX_PLY {
+            return self.evaluator.evaluate(board);
+        }
+
+        let key = board.hash_key();
+        let entry = self.tt.probe(key);
+        if let Some(entry) = entry
+            && entry.depth >= depth
+        {
+            let value = entry.value(ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => value >= beta,
+                Bound::Upper => value <= alpha,
+            };
+            if cutoff {
+                if let Some(mv) = moves
+                    .iter()
+                    .find(|mv| entry.best_move.is_some_and(|key| key.matches(mv.data())))
+                {
+                    pv.push(mv.data());
+                }
+                return value;
+            }
+        }
+
+        if let Some(probe) = self
+            .tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase::probe(tablebase.as_ref(), board))
+        {
+            let value = tablebase::value(probe, ply);
+            // without a distance to mate a win is only known to be at least that value
+            let bound = match (probe.wdl, probe.distance) {
+                (Wdl::Win, None) => Bound::Lower,
+                (Wdl::Loss, None) => Bound::Upper,
+                _ => Bound::Exact,
+            };
+            self.tt.store(key, MAX_PLY, ply, bound, value, None);
+            return value;
+        }
+
+        let pv_node = beta - alpha > 1;
+        let static_eval = if in_check {
+            -INFINITY
+        } else {
+            self.evaluator.evaluate(board)
+        };
+        let prunable = !pv_node && !in_check && beta.abs() < TABLEBASE_BOUND;
+
+        if prunable && let Some(value) = self.prune(board, depth, ply, beta, static_eval, prev) {
+            return value;
+        }
+
+        let futile = self.config.futility
+            && prunable
+            && depth <= 3
+            && static_eval + FUTILITY_MARGIN.0 + FUTILITY_MARGIN.1 * depth <= alpha;
+
+        let tt_move = entry.and_then(|entry| entry.best_move);
+        let picker = MovePicker::new(board, &moves, tt_move, &self.heuristics, prev, ply);
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+        let mut quiets_tried = Vec::new();
+        for (i, mv) in picker.enumerate() {
+            let child = board.apply_move(&mv);
+            let quiet = !mv.data().is_capture() && !child.is_in_check();
+            if futile && i > 0 && quiet {
+                continue;
+            }
+
+            let reduction =
+                if self.config.late_move_reductions && depth >= 3 && i >= 3 && quiet && !in_check {
+                    1 + i32::from(i >= 8) + depth / 8
+                } else {
+                    0
+                };
+            let mut value = if reduction > 0 {
+                -self.negamax(
+                    &child,
+                    depth - 1 - reduction,
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                    Some(mv.data()),
+                    &mut child_pv,
+                )
+            } else {
+                INFINITY
+            };
+            // moves without a reduction, and reduced moves beating alpha, get a full search
+            if value > alpha && !self.stopped {
+                value = -self.negamax(
+                    &child,
+                    depth - 1,
+                    ply + 1,
+                    -beta,
+                    -alpha,
+                    Some(mv.data()),
+                    &mut child_pv,
+                );
+            }
+            if self.stopped {
+                return 0;
+            }
+            if value > best {
+                best = value;
+                if value > alpha {
+                    alpha = value;
+                    best_move = Some(MoveKey::new(mv.data()));
+                    pv.clear();
+                    pv.push(mv.data());
+                    pv.extend_from_slice(&child_pv);
+                    if value >= beta {
+                        if !mv.data().is_capture() {
+                            self.heuristics
+                                .cutoff(mv.data(), prev, ply, depth, &quiets_tried);
+                        }
+                        break;
+                    }
+                }
+            }
+            if !mv.data().is_capture() {
+                quiets_tried.push(mv.data());
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(key, depth, ply, bound, best, best_move);
+        best
+    }
+
+    /// Cuts off a node outside the principal variation and out of check without
+    /// searching its moves, by reverse futility or null move pruning.
+    fn prune<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        depth: i32,
+        ply: i32,
+        beta: i32,
+        static_eval: i32,
+        prev: Option<Move>,
+    ) -> Option<i32> {
+        if self.config.reverse_futility
+            && depth <= 6
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth >= beta
+        {
+            return Some(static_eval);
+        }
+
+        if self.config.null_move
+            && prev.is_some()
+            && depth >= 3
+            && static_eval >= beta
+            && has_pieces(board)
+        {
+            let reduction = 3 + depth / 6;
+            let value = -self.negamax(
+                &board.null_move(),
+                depth - 1 - reduction,
+                ply + 1,
+                -beta,
+                -beta + 1,
+                None,
+                &mut Vec::new(),
+            );
+            if self.stopped {
+                return Some(0);
+            }
+            if value >= beta {
+                // unproven wins from a null move search are not trusted
+                return Some(if value >= TABLEBASE_BOUND {
+                    beta
+                } else {
+                    value
+                });
+            }
+        }
+        None
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.stopped = true;
+        } else if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.stopped = self.stop.load(Ordering::Relaxed)
+                || self
+                    .deadline
+                    .is_some_and(|deadline| self.start.elapsed() >= deadline);
+        }
+        self.stopped
+    }
+}
+
+/// Picks the best move most threads agree on, each thread voting with the depth
+/// of its result times its score above the worst one.
+fn vote<S: Side>(result: SearchResult<S>, helpers: Vec<SearchResult<S>>) -> SearchResult<S> {
+    let nodes = result.nodes + helpers.iter().map(|result| result.nodes).sum::<u64>();
+    if result.depth == 0 {
+        return SearchResult { nodes, ..result };
+    }
+    let mut results: Vec<_> = iter::once(result)
+        .chain(helpers)
+        .filter(|result| result.depth > 0)
+        .collect();
+    let key = |result: &SearchResult<S>| result.best_move.map(|mv| MoveKey::new(mv.data()));
+    let worst = results
+        .iter()
+        .map(|result| result.score.value())
+        .min()
+        .unwrap_or(0);
+    let mut votes: Vec<(Option<MoveKey>, i64)> = Vec::new();
+    for result in &results {
+        let weight = i64::from(result.score.value() - worst + 20) * i64::from(result.depth);
+        match votes.iter_mut().find(|(mv, _)| *mv == key(result)) {
+            Some((_, total)) => *total += weight,
+            None => votes.push((key(result), weight)),
+        }
+    }
+    // ties go to the move voted for first, that of the main thread
+    let winner = votes
+        .iter()
+        .rev()
+        .max_by_key(|(_, total)| *total)
+        .and_then(|(mv, _)| *mv);
+    let best = (0..results.len())
+        .filter(|&i| key(&results[i]) == winner)
+        .max_by_key(|&i| (results[i].depth, std::cmp::Reverse(i)))
+        .unwrap_or(0);
+    SearchResult {
+        nodes,
+        ..results.swap_remove(best)
+    }
+}
+
+/// Whether the side on move has a piece other than its king and pawns, without
+/// which passing in null move pruning is unsafe because of zugzwang.
+fn has_pieces<S: Side + Clone>(board: &Board<S>) -> bool {
+    Pos::all().filter_map(|pos| board.at(pos)).any(|piece| {
+        piece.team() == S::TEAM && !matches!(piece.piece_type(), PieceType::King | PieceType::Pawn)
+    })
+}
+
+/// Value of a position without legal moves, mate scores prefer shorter mates.
+fn terminal_value<S: Side + Clone>(board: &Board<S>, ply: i32) -> i32 {
+    if board.is_in_check() { -MATE + ply } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{Position, Team, White};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn finds_back_rank_mate() {
+        let board = Board::<White>::from_str(concat!(
+            "wR,  ,  ,  ,  ,  ,wK,  \n",
+            "  ,  ,  ,  ,  ,wP,wP,wP\n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,bP,bP\n",
+            "  ,  ,  ,  ,  ,  ,  ,bK"
+        ))
+        .unwrap();
+        let result = Searcher::new().search(
+            &board,
+            SearchLimits {
+                depth: Some(2),
+                ..SearchLimits::default()
+            },
+        );
+        assert_eq!(result.score, Score::Mate(1));
+        assert_eq!(result.depth, 2);
+        let best = result.best_move.unwrap();
+        assert_eq!(best.data().to().pos(), Pos::new(7, 0).unwrap());
+        assert_eq!(result.pv.len(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn quiescence_sees_recapture() {
+        // a depth 1 search would win the pawn on d5 without seeing exd5
+        let board = Board::<White>::from_str(concat!(
+            "  ,  ,  ,wQ,  ,  ,  ,wK\n",
+            "  ,  ,  ,  ,  ,  ,wP,wP\n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,bP,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,bP,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,bP,bP\n",
+            "  ,  ,  ,  ,  ,  ,  ,bK"
+        ))
+        .unwrap();
+        let result = Searcher::new().search(
+            &board,
+            SearchLimits {
+                depth: Some(1),
+                ..SearchLimits::default()
+            },
+        );
+        let best = result.best_move.unwrap();
+        assert_ne!(best.data().to().pos(), Pos::new(4, 3).unwrap());
+    }
+
+    #[test]
+    fn selectivity_saves_nodes() {
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..SearchLimits::default()
+        };
+        let selective = Searcher::new().search(&Board::default(), limits);
+        let mut plain = Searcher::new();
+        plain.set_config(SearchConfig {
+            null_move: false,
+            late_move_reductions: false,
+            reverse_futility: false,
+            futility: false,
+            check_extensions: false,
+            aspiration_windows: false,
+        });
+        let full = plain.search(&Board::default(), limits);
+        assert_eq!(selective.depth, 4);
+        assert!(selective.nodes < full.nodes);
+    }
+
+    #[test]
+    fn multi_pv_ranks_distinct_moves() {
+        let mut searcher = Searcher::new();
+        searcher.set_multi_pv(3);
+        let result = searcher.search(
+            &Board::default(),
+            SearchLimits {
+                depth: Some(2),
+                ..SearchLimits::default()
+            },
+        );
+        assert_eq!(result.lines.len(), 3);
+        let firsts: Vec<_> = result
+            .lines
+            .iter()
+            .map(|line| MoveKey::new(line.pv[0]))
+            .collect();
+        assert_eq!(firsts[0], MoveKey::new(result.pv[0]));
+        assert!(firsts[0] != firsts[1] && firsts[1] != firsts[2] && firsts[0] != firsts[2]);
+        for pair in result.lines.windows(2) {
+            let [Score::Centipawns(a), Score::Centipawns(b)] = [pair[0].score, pair[1].score]
+            else {
+                panic!("unexpected mate score");
+            };
+            assert!(a >= b);
+            assert_eq!(pair[1].depth, 2);
+            assert!(pair[1].nodes > 0);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn threads_share_the_search() {
+        let board = Board::<White>::from_str(concat!(
+            "wR,  ,  ,  ,  ,  ,wK,  \n",
+            "  ,  ,  ,  ,  ,wP,wP,wP\n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,  ,  \n",
+            "  ,  ,  ,  ,  ,  ,bP,bP\n",
+            "  ,  ,  ,  ,  ,  ,  ,bK"
+        ))
+        .unwrap();
+        let mut searcher = Searcher::new();
+        searcher.set_threads(4);
+        let result = searcher.search(
+            &board,
+            SearchLimits {
+                depth: Some(3),
+                ..SearchLimits::default()
+            },
+        );
+        assert_eq!(result.score, Score::Mate(1));
+        assert_eq!(
+            result.best_move.unwrap().data().to().pos(),
+            Pos::new(7, 0).unwrap()
+        );
+        assert!(result.nodes > 0);
+    }
+
+    #[test]
+    fn stop_flag_cancels_search() {
+        let mut searcher = Searcher::new();
+        searcher.stop_flag().store(true, Ordering::Relaxed);
+        let result = searcher.search(&Board::default(), SearchLimits::default());
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move.is_some());
+    }
+
+    /// Tablebase where the side with more pieces wins and equal sides draw.
+    #[derive(Debug)]
+    struct MaterialTablebase;
+
+    impl Tablebase for MaterialTablebase {
+        fn max_pieces(&self) -> usize {
+            4
+        }
+
+        fn probe(&self, position: &Position) -> Option<Probe> {
+            let team = position.team();
+            let fen = position.fen();
+            let placement = fen.split_whitespace().next()?;
+            let own = placement
+                .chars()
+                .filter(|c| c.is_alphabetic() && c.is_ascii_uppercase() == (team == Team::White))
+                .count();
+            let other = placement.chars().filter(|c| c.is_alphabetic()).count() - own;
+            let wdl = match own.cmp(&other) {
+                std::cmp::Ordering::Less => Wdl::Loss,
+                std::cmp::Ordering::Equal => Wdl::Draw,
+                std::cmp::Ordering::Greater => Wdl::Win,
+            };
+            Some(Probe {
+                wdl,
+                distance: None,
+            })
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn tablebase_picks_the_winning_root_move() {
+        let board = Board::<White>::from_fen("3r3k/8/8/8/8/8/8/3Q3K w").unwrap();
+        let mut searcher = Searcher::new();
+        searcher.set_tablebase(Some(Arc::new(MaterialTablebase)));
+        let result = searcher.search(
+            &board,
+            SearchLimits {
+                depth: Some(2),
+                ..SearchLimits::default()
+            },
+        );
+        assert_eq!(
+            result.best_move.map(|mv| mv.data().to_string()).unwrap(),
+            "d1d8"
+        );
+        assert_eq!(result.score, Score::Tablebase(1));
+        assert_eq!(result.score.to_string(), "cp 19999");
+
+        let lost = Probe {
+            wdl: Wdl::Loss,
+            distance: Some(2),
+        };
+        assert_eq!(
+            Score::from_value(-tablebase::value(lost, 1)),
+            Score::Mate(2)
+        );
+    }
+}
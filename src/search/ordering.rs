@@ -0,0 +1,213 @@
+//! Move ordering: killer moves, countermoves, history and staged move picking.
+
+use crate::movement::{MAX_MOVES, MoveList};
+use crate::piece::team::Side;
+use crate::search::{MAX_PLY, MoveKey};
+use crate::{Board, LegalMove, Move, Piece};
+
+/// Bound of history scores, the gravity update keeps them within it.
+const MAX_HISTORY: i32 = 16_384;
+
+// Stage of a move, the picker hands out moves of a higher stage first.
+const TT_MOVE: i32 = 4_000_000;
+const GOOD_CAPTURE: i32 = 3_000_000;
+const KILLER: i32 = 2_000_000;
+const COUNTERMOVE: i32 = 1_900_000;
+const QUIET: i32 = 0;
+const BAD_CAPTURE: i32 = -3_000_000;
+
+/// Ordering knowledge gathered from cutoffs during a search.
+#[derive(Clone, Debug)]
+pub struct Heuristics {
+    killers: [[Option<MoveKey>; 2]; MAX_PLY as usize + 1],
+    /// Quiet move scores by team, starting square and destination square.
+    history: Vec<[[i32; 64]; 64]>,
+    /// Reply refuting a move, by the moved piece and its destination square.
+    countermoves: [[Option<MoveKey>; 64]; 12],
+}
+
+impl Default for Heuristics {
+    fn default() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_PLY as usize + 1],
+            history: vec![[[0; 64]; 64]; 2],
+            countermoves: [[None; 64]; 12],
+        }
+    }
+}
+
+const fn piece_index(piece: Piece) -> usize {
+    piece.team().index() * 6 + piece.piece_type().index()
+}
+
+fn ply_index(ply: i32) -> usize {
+    usize::try_from(ply.clamp(0, MAX_PLY)).unwrap_or(0)
+}
+
+impl Heuristics {
+    /// Keeps the history of earlier searches at half weight and forgets killers.
+    pub fn new_search(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY as usize + 1];
+        for score in self.history.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn history(&self, mv: Move) -> i32 {
+        mv.from().content().map_or(0, |piece| {
+            self.history[piece.team().index()][mv.from().pos().index()][mv.to().pos().index()]
+        })
+    }
+
+    fn countermove(&self, prev: Option<Move>) -> Option<MoveKey> {
+        let prev = prev?;
+        let piece = prev.from().content()?;
+        self.countermoves[piece_index(piece)][prev.to().pos().index()]
+    }
+
+    /// Moves the history score of `mv` towards `bonus`, slower the closer it is
+    /// to [`MAX_HISTORY`].
+    fn update_history(&mut self, mv: Move, bonus: i32) {
+        if let Some(piece) = mv.from().content() {
+            let score = &mut self.history[piece.team().index()][mv.from().pos().index()]
+                [mv.to().pos().index()];
+            let bonus = bonus.clamp(-MAX_HISTORY, MAX_HISTORY);
+            *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+        }
+    }
+
+    /// Rewards the quiet move `mv` that caused a beta cutoff at `ply` and punishes
+    /// the quiet moves searched before it.
+    pub fn cutoff(&mut self, mv: Move, prev: Option<Move>, ply: i32, depth: i32, tried: &[Move]) {
+        let killers = &mut self.killers[ply_index(ply)];
+        let key = MoveKey::new(mv);
+        if killers[0] != Some(key) {
+            killers[1] = killers[0];
+            killers[0] = Some(key);
+        }
+        if let Some(prev) = prev
+            && let Some(piece) = prev.from().content()
+        {
+            self.countermoves[piece_index(piece)][prev.to().pos().index()] = Some(key);
+        }
+        let bonus = depth * depth;
+        self.update_history(mv, bonus);
+        for &quiet in tried {
+            self.update_history(quiet, -bonus);
+        }
+    }
+}
+
+/// Hands out the moves of a node best first: the transposition table move, good
+/// captures by MVV-LVA, killer moves, the countermove, quiet moves by history and
+/// captures losing material last.
+pub struct MovePicker<S: Side> {
+    moves: MoveList<S>,
+    scores: [i32; MAX_MOVES],
+    next: usize,
+}
+
+impl<S: Side + Clone> MovePicker<S> {
+    pub fn new(
+        board: &Board<S>,
+        moves: &MoveList<S>,
+        tt_move: Option<MoveKey>,
+        heuristics: &Heuristics,
+        prev: Option<Move>,
+        ply: i32,
+    ) -> Self {
+        let killers = heuristics.killers[ply_index(ply)];
+        let countermove = heuristics.countermove(prev);
+        let mut scores = [0; MAX_MOVES];
+        for (score, mv) in scores.iter_mut().zip(moves.iter()) {
+            let key = MoveKey::new(mv.data());
+            *score = if tt_move == Some(key) {
+                TT_MOVE
+            } else if mv.data().is_capture() {
+                let victim = mv
+                    .data()
+                    .to()
+                    .content()
+                    .map_or(0, |p| p.piece_type().value());
+                let attacker = mv
+                    .data()
+                    .from()
+                    .content()
+                    .map_or(0, |p| p.piece_type().value());
+                let mvv_lva = victim * 8 - attacker / 100;
+                if board.see(mv) >= 0 {
+                    GOOD_CAPTURE + mvv_lva
+                } else {
+                    BAD_CAPTURE + mvv_lva
+                }
+            } else if killers[0] == Some(key) {
+                KILLER + 1
+            } else if killers[1] == Some(key) {
+                KILLER
+            } else if countermove == Some(key) {
+                COUNTERMOVE
+            } else {
+                QUIET + heuristics.history(mv.data())
+            };
+        }
+        Self {
+            moves: *moves,
+            scores,
+            next: 0,
+        }
+    }
+}
+
+impl<S: Side> Iterator for MovePicker<S> {
+    type Item = LegalMove<S>;
+
+    /// Selects the best of the remaining moves, so that a node cut off early does
+    /// not pay for ordering all of its moves.
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.moves.len();
+        let best = (self.next..len).max_by_key(|&i| (self.scores[i], std::cmp::Reverse(i)))?;
+        self.moves.swap(self.next, best);
+        self.scores.swap(self.next, best);
+        self.next += 1;
+        Some(self.moves[self.next - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::White;
+
+    #[test]
+    fn picker_puts_tt_move_and_killers_first() {
+        let board = Board::<White>::default();
+        let moves = board.team_legal_moves();
+        let tt_move = MoveKey::new(moves[5].data());
+        let mut heuristics = Heuristics::default();
+        heuristics.cutoff(moves[7].data(), None, 3, 4, &[moves[2].data()]);
+
+        let picked: Vec<_> = MovePicker::new(&board, &moves, Some(tt_move), &heuristics, None, 3)
+            .map(|mv| MoveKey::new(mv.data()))
+            .collect();
+        assert_eq!(picked.len(), moves.len());
+        assert_eq!(picked[0], tt_move);
+        assert_eq!(picked[1], MoveKey::new(moves[7].data()));
+        assert_eq!(picked.last(), Some(&MoveKey::new(moves[2].data())));
+    }
+
+    #[test]
+    fn history_stays_bounded() {
+        let board = Board::<White>::default();
+        let mv = board.team_legal_moves()[0].data();
+        let mut heuristics = Heuristics::default();
+        for _ in 0..1000 {
+            heuristics.cutoff(mv, None, 0, 40, &[]);
+        }
+        assert!(heuristics.history(mv) <= MAX_HISTORY);
+        assert!(heuristics.history(mv) > MAX_HISTORY / 2);
+    }
+}
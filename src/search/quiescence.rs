@@ -0,0 +1,80 @@
+//! Quiescence search resolving captures at the leaves of the main search.
+
+use crate::Board;
+use crate::piece::team::Side;
+use crate::search::{INFINITY, MAX_PLY, Searcher, terminal_value};
+
+/// Margin on top of the captured piece's value below which a capture cannot
+/// bring the score back up to alpha.
+const DELTA_MARGIN: i32 = 200;
+
+impl Searcher {
+    /// Searches captures until the position is quiet, so that the evaluation is
+    /// not taken in the middle of an exchange. A side in check searches all its
+    /// evasions instead.
+    pub(super) fn quiescence<S: Side + Clone>(
+        &mut self,
+        board: &Board<S>,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        let mut moves = board.team_legal_moves();
+        if moves.is_empty() {
+            return terminal_value(board, ply);
+        }
+        if ply >= MAX_PLY {
+            return self.evaluator.evaluate(board);
+        }
+
+        let in_check = board.is_in_check();
+        let stand_pat = if in_check {
+            -INFINITY
+        } else {
+            self.evaluator.evaluate(board)
+        };
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        moves.sort_mvv_lva();
+        let mut best = stand_pat;
+        for mv in &moves {
+            if !in_check {
+                // captures come first, the rest of the moves are quiet
+                if !mv.data().is_capture() {
+                    break;
+                }
+                let gain = mv
+                    .data()
+                    .to()
+                    .content()
+                    .map_or(0, |piece| piece.piece_type().value());
+                if stand_pat + gain + DELTA_MARGIN < alpha || board.see(mv) < 0 {
+                    continue;
+                }
+            }
+
+            let value = -self.quiescence(&board.apply_move(mv), ply + 1, -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+            if value > best {
+                best = value;
+                if value > alpha {
+                    alpha = value;
+                    if value >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,113 @@
+//! Endgame tablebases probed by the search.
+//!
+//! The search asks a [`Tablebase`] for the result of positions with few enough
+//! pieces. At the root it keeps only the moves keeping the best result, inside
+//! the tree a known result ends the search of a node.
+
+use std::fmt;
+
+use crate::piece::team::Side;
+use crate::search::{MATE, MAX_PLY, TABLEBASE_WIN};
+use crate::{Board, MoveList, Pos, Position};
+
+/// Game-theoretic result for the side on move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// Result of a position found in a tablebase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe {
+    pub wdl: Wdl,
+    /// Plies until mate of a won or lost position, `None` when the tablebase
+    /// only knows the result.
+    pub distance: Option<u32>,
+}
+
+impl Probe {
+    /// The result for the other side, one ply earlier.
+    const fn parent(self) -> Self {
+        let wdl = match self.wdl {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        };
+        let distance = match self.distance {
+            Some(distance) => Some(distance + 1),
+            None => None,
+        };
+        Self { wdl, distance }
+    }
+
+    /// Orders results from worst to best: losing late beats losing early, winning
+    /// early beats winning late, and a known distance beats an unknown one.
+    fn rank(self) -> (Wdl, i64) {
+        let distance = self.distance.map_or(i64::MAX, i64::from);
+        match self.wdl {
+            Wdl::Loss => (Wdl::Loss, -distance),
+            Wdl::Draw => (Wdl::Draw, 0),
+            Wdl::Win => (Wdl::Win, -distance),
+        }
+    }
+}
+
+/// Source of endgame results, shared by all search threads.
+pub trait Tablebase: fmt::Debug + Send + Sync {
+    /// Most pieces, kings included, of the positions it holds.
+    fn max_pieces(&self) -> usize;
+
+    /// Result of `position`, `None` when it is not held.
+    fn probe(&self, position: &Position) -> Option<Probe>;
+}
+
+/// Result of `board` when it has at most [`Tablebase::max_pieces`] pieces.
+pub fn probe<S: Side + Clone>(tablebase: &dyn Tablebase, board: &Board<S>) -> Option<Probe> {
+    let pieces = Pos::all().filter(|&pos| board.at(pos).is_some()).count();
+    if pieces > tablebase.max_pieces() {
+        return None;
+    }
+    tablebase.probe(&Position::from(board))
+}
+
+/// Search value of a probed result at `ply`, a mate score when the distance to
+/// mate is known and within [`MAX_PLY`], otherwise a tablebase win or loss.
+pub fn value(probe: Probe, ply: i32) -> i32 {
+    let win = probe
+        .distance
+        .and_then(|distance| i32::try_from(distance).ok())
+        .map(|distance| ply.saturating_add(distance))
+        .filter(|&plies| plies < MAX_PLY)
+        .map_or(TABLEBASE_WIN - ply, |plies| MATE - plies);
+    match probe.wdl {
+        Wdl::Win => win,
+        Wdl::Draw => 0,
+        Wdl::Loss => -win,
+    }
+}
+
+/// The root `moves` keeping the best result, all of them when a result after
+/// some move is unknown.
+pub fn root_moves<S: Side + Clone>(
+    tablebase: &dyn Tablebase,
+    board: &Board<S>,
+    moves: &MoveList<S>,
+) -> MoveList<S> {
+    let ranks: Option<Vec<_>> = moves
+        .iter()
+        .map(|mv| Some(probe(tablebase, &board.apply_move(mv))?.parent().rank()))
+        .collect();
+    let Some(ranks) = ranks else {
+        return *moves;
+    };
+    let best = ranks.iter().max();
+    let mut kept = MoveList::new();
+    for (mv, rank) in moves.iter().zip(&ranks) {
+        if Some(rank) == best {
+            kept.push(*mv);
+        }
+    }
+    kept
+}
@@ -0,0 +1,162 @@
+//! Time management for games played on a clock.
+
+use std::time::Duration;
+
+use crate::Move;
+use crate::search::MoveKey;
+
+/// Time kept back for communication and move overhead.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+/// Moves assumed to remain in sudden death games.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Largest multiple of the soft deadline a search may run for.
+const MAX_STRETCH: u32 = 4;
+/// Iterations with an unchanged best move after which the soft deadline stops shrinking.
+const MAX_STABILITY: u32 = 4;
+/// Score drop in centipawns at which the soft deadline doubles.
+const MAX_SCORE_DROP: u32 = 100;
+/// Divisor of the lead on the opponent's clock that is spent over the moves
+/// to go, or of the deficit that is saved.
+const LEAD_DIVISOR: u32 = 2;
+
+/// Clock of the side on move.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Clock {
+    pub remaining: Duration,
+    pub increment: Duration,
+    /// Moves until the next time control, `None` in sudden death.
+    pub moves_to_go: Option<u32>,
+    /// Time left on the opponent's clock, `None` when unknown.
+    pub opponent: Option<Duration>,
+}
+
+/// Splits the time on a clock into deadlines for one search.
+///
+/// The search should not start another iteration past the soft deadline and must
+/// stop at the hard one. The soft deadline takes a share of a lead on the
+/// opponent's clock, shrinks while the best move stays the same and grows when
+/// the score drops.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+    best_move: Option<MoveKey>,
+    stable: u32,
+    value: Option<i32>,
+}
+
+impl TimeManager {
+    #[must_use]
+    pub fn new(clock: &Clock) -> Self {
+        let available = clock.remaining.saturating_sub(MOVE_OVERHEAD);
+        let moves_to_go = clock.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let opponent = clock.opponent.unwrap_or(clock.remaining);
+        let lead = clock.remaining.saturating_sub(opponent) / LEAD_DIVISOR;
+        let deficit = opponent.saturating_sub(clock.remaining) / LEAD_DIVISOR;
+        let budget = (available + lead).saturating_sub(deficit);
+        let soft = budget / moves_to_go + clock.increment * 3 / 4;
+        let hard = (soft * MAX_STRETCH).min(available * 4 / 5);
+        Self {
+            soft: soft.min(hard),
+            hard,
+            best_move: None,
+            stable: 0,
+            value: None,
+        }
+    }
+
+    /// Deadline for starting another iteration, before any adjustment.
+    #[must_use]
+    pub const fn soft(&self) -> Duration {
+        self.soft
+    }
+
+    /// Deadline at which the search has to stop.
+    #[must_use]
+    pub const fn hard(&self) -> Duration {
+        self.hard
+    }
+
+    /// Records a completed iteration with its best move and value and returns
+    /// whether there is time left for another one.
+    pub fn next_iteration(&mut self, best_move: Move, value: i32, elapsed: Duration) -> bool {
+        let key = MoveKey::new(best_move);
+        if self.best_move == Some(key) {
+            self.stable = (self.stable + 1).min(MAX_STABILITY);
+        } else {
+            self.best_move = Some(key);
+            self.stable = 0;
+        }
+        let drop = self
+            .value
+            .map_or(0, |previous| u32::try_from(previous - value).unwrap_or(0))
+            .min(MAX_SCORE_DROP);
+        self.value = Some(value);
+
+        let scale = (100 - 10 * self.stable) * (MAX_SCORE_DROP + drop) / MAX_SCORE_DROP;
+        elapsed < (self.soft * scale / 100).min(self.hard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Board, White};
+
+    fn manager() -> TimeManager {
+        TimeManager::new(&Clock {
+            remaining: Duration::from_mins(1) + MOVE_OVERHEAD,
+            increment: Duration::from_secs(1),
+            moves_to_go: Some(20),
+            opponent: None,
+        })
+    }
+
+    #[test]
+    fn deadlines_split_the_clock() {
+        let time = manager();
+        assert_eq!(time.soft(), Duration::from_millis(3750));
+        assert_eq!(time.hard(), Duration::from_secs(15));
+
+        let last_move = TimeManager::new(&Clock {
+            remaining: Duration::from_secs(1),
+            ..Clock::default()
+        });
+        assert!(last_move.soft() <= last_move.hard());
+        assert!(last_move.hard() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn leads_on_the_opponent_clock_are_spent() {
+        let clock = |opponent| Clock {
+            remaining: Duration::from_mins(1) + MOVE_OVERHEAD,
+            moves_to_go: Some(20),
+            opponent: Some(opponent),
+            ..Clock::default()
+        };
+        let even = TimeManager::new(&clock(Duration::from_mins(1) + MOVE_OVERHEAD));
+        assert_eq!(even.soft(), Duration::from_secs(3));
+        // half of the 20 second lead spread over the 20 moves
+        let ahead = TimeManager::new(&clock(Duration::from_secs(40) + MOVE_OVERHEAD));
+        assert_eq!(ahead.soft(), Duration::from_millis(3500));
+        let behind = TimeManager::new(&clock(Duration::from_secs(80) + MOVE_OVERHEAD));
+        assert_eq!(behind.soft(), Duration::from_millis(2500));
+        assert!(behind.hard() < ahead.hard());
+    }
+
+    #[test]
+    fn stable_moves_stop_early_and_dropping_scores_extend() {
+        let moves = Board::<White>::default().team_legal_moves();
+        let (first, second) = (moves[0].data(), moves[1].data());
+        let elapsed = Duration::from_millis(3500);
+
+        let mut time = manager();
+        assert!(time.next_iteration(first, 20, elapsed));
+        assert!(!time.next_iteration(first, 20, elapsed));
+
+        let mut time = manager();
+        time.next_iteration(first, 20, elapsed);
+        assert!(time.next_iteration(first, -60, elapsed));
+        assert!(time.next_iteration(second, -60, Duration::from_millis(3600)));
+    }
+}
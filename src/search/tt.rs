@@ -0,0 +1,242 @@
+//! Transposition table caching search results by position hash.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+use crate::Move;
+use crate::search::{MATE, MAX_PLY, TABLEBASE_BOUND};
+
+/// Relation of a stored value to the true value of the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, the true value is at least the stored one.
+    Lower,
+    /// The search failed low, the true value is at most the stored one.
+    Upper,
+}
+
+/// Move reduced to its starting and destination square indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveKey(u16);
+
+impl MoveKey {
+    #[must_use]
+    pub fn new(mv: Move) -> Self {
+        let from = mv.from().pos().index();
+        let to = mv.to().pos().index();
+        Self(u16::try_from(from << 6 | to).unwrap_or(0))
+    }
+
+    #[must_use]
+    pub fn matches(self, mv: Move) -> bool {
+        self == Self::new(mv)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub depth: i32,
+    pub bound: Bound,
+    value: i32,
+    pub best_move: Option<MoveKey>,
+    generation: u8,
+}
+
+impl Entry {
+    /// Stored value with mate and tablebase scores measured from the node at `ply`.
+    #[must_use]
+    pub const fn value(&self, ply: i32) -> i32 {
+        if self.value >= TABLEBASE_BOUND {
+            self.value - ply
+        } else if self.value <= -TABLEBASE_BOUND {
+            self.value + ply
+        } else {
+            self.value
+        }
+    }
+
+    /// Packs the entry into 64 bits, an empty slot reads as 0.
+    fn pack(self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        let best_move = self.best_move.map_or(0, |mv| u64::from(mv.0) | 1 << 12);
+        u64::from(self.depth.clamp(0, MAX_PLY).unsigned_abs())
+            | bound << 8
+            | u64::from(self.value.clamp(-MATE, MATE).cast_unsigned() & 0xffff) << 10
+            | best_move << 26
+            | u64::from(self.generation) << 39
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn unpack(data: u64) -> Option<Self> {
+        let bound = match (data >> 8) & 3 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let best_move = (data >> 26) & 0x1fff;
+        Some(Self {
+            depth: i32::from(data as u8),
+            bound,
+            value: i32::from(((data >> 10) as u16).cast_signed()),
+            best_move: (best_move >> 12 == 1).then_some(MoveKey(best_move as u16 & 0xfff)),
+            generation: (data >> 39) as u8,
+        })
+    }
+}
+
+/// Fixed-size hash table of search results shared by all search threads. An
+/// entry is replaced by a search of the same or greater depth, or by any result
+/// of a newer search.
+///
+/// Slots are written without locks, each holding the packed entry and the key
+/// xor the entry. An entry torn by a concurrent write fails the key check and
+/// reads as missing.
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Creates a table taking up about `megabytes` of memory.
+    #[must_use]
+    pub fn new(megabytes: usize) -> Self {
+        let size = (megabytes * 1024 * 1024 / size_of::<[AtomicU64; 2]>()).max(1);
+        Self {
+            entries: (0..size)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    /// Empties the table, e.g. before a new game.
+    pub fn clear(&self) {
+        for slot in &self.entries {
+            slot[0].store(0, Ordering::Relaxed);
+            slot[1].store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks entries stored so far as old, called at the start of every search.
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn slot(&self, key: u64) -> &[AtomicU64; 2] {
+        &self.entries[usize::try_from(key % self.entries.len() as u64).unwrap_or(0)]
+    }
+
+    #[must_use]
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = self.slot(key);
+        let checked = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+        if checked ^ data == key {
+            Entry::unpack(data)
+        } else {
+            None
+        }
+    }
+
+    /// Stores the result of a search at `ply` from the root, `value` having mate
+    /// and tablebase scores measured from the root.
+    pub fn store(
+        &self,
+        key: u64,
+        depth: i32,
+        ply: i32,
+        bound: Bound,
+        value: i32,
+        best_move: Option<MoveKey>,
+    ) {
+        let slot = self.slot(key);
+        let generation = self.generation.load(Ordering::Relaxed);
+        if let Some(old) = Entry::unpack(slot[1].load(Ordering::Relaxed))
+            && old.generation == generation
+            && old.depth > depth
+        {
+            return;
+        }
+        let value = if value >= TABLEBASE_BOUND {
+            value + ply
+        } else if value <= -TABLEBASE_BOUND {
+            value - ply
+        } else {
+            value
+        };
+        let data = Entry {
+            depth,
+            bound,
+            value,
+            best_move,
+            generation,
+        }
+        .pack();
+        slot[0].store(key ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("entries", &self.entries.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mate_scores_are_stored_relative_to_the_node() {
+        let tt = TranspositionTable::new(1);
+        // mate found 5 plies below a node at ply 3
+        tt.store(42, 4, 3, Bound::Exact, MATE - 8, None);
+        let entry = tt.probe(42);
+        assert_eq!(entry.map(|entry| entry.value(1)), Some(MATE - 6));
+        assert_eq!(tt.probe(43), None);
+        // likewise a tablebase loss without distance to mate
+        tt.store(44, 4, 3, Bound::Upper, -TABLEBASE_BOUND - 5, None);
+        let entry = tt.probe(44);
+        assert_eq!(
+            entry.map(|entry| entry.value(1)),
+            Some(-TABLEBASE_BOUND - 7)
+        );
+    }
+
+    #[test]
+    fn deeper_entries_are_kept_within_a_search() {
+        let tt = TranspositionTable::new(1);
+        tt.store(42, 6, 0, Bound::Lower, 10, None);
+        tt.store(42, 2, 0, Bound::Upper, 20, None);
+        assert_eq!(tt.probe(42).map(|entry| entry.depth), Some(6));
+        tt.new_search();
+        tt.store(42, 2, 0, Bound::Upper, 20, None);
+        assert_eq!(tt.probe(42).map(|entry| entry.depth), Some(2));
+        tt.clear();
+        assert_eq!(tt.probe(42), None);
+    }
+
+    #[test]
+    fn torn_entries_read_as_missing() {
+        let tt = TranspositionTable::new(1);
+        let best_move = Some(MoveKey(0o1234));
+        tt.store(42, 5, 0, Bound::Lower, -MATE + 3, best_move);
+        let entry = tt.probe(42);
+        assert_eq!(entry.map(|entry| entry.value(0)), Some(-MATE + 3));
+        assert_eq!(entry.and_then(|entry| entry.best_move), best_move);
+        // another thread overwrote the data half of the slot only
+        tt.slot(42)[1].fetch_xor(1 << 10, Ordering::Relaxed);
+        assert_eq!(tt.probe(42), None);
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::str::FromStr;
+
 use crate::piece::team::Side;
 use crate::{Board, piece::Piece};
 
@@ -8,6 +11,13 @@ pub struct Pos {
 }
 
 impl Pos {
+    pub(crate) const A1: Self = Self { file: 0, rank: 0 };
+    pub(crate) const E1: Self = Self { file: 4, rank: 0 };
+    pub(crate) const H1: Self = Self { file: 7, rank: 0 };
+    pub(crate) const A8: Self = Self { file: 0, rank: 7 };
+    pub(crate) const E8: Self = Self { file: 4, rank: 7 };
+    pub(crate) const H8: Self = Self { file: 7, rank: 7 };
+
     #[must_use]
     pub fn new(rank: isize, file: isize) -> Option<Self> {
         let rank = usize::try_from(rank).ok()?;
@@ -19,6 +29,11 @@ impl Pos {
         }
     }
 
+    /// All positions in order a1, b1, ..., h1, a2, ..., h8.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..8).flat_map(|rank| (0..8).map(move |file| Self { file, rank }))
+    }
+
     #[must_use]
     pub fn checked_add(self, (r, c): (isize, isize)) -> Option<Self> {
         Self::new(
@@ -27,6 +42,24 @@ impl Pos {
         )
     }
 
+    /// Index of the square counting a1 = 0, b1 = 1, ..., h8 = 63.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.rank * 8 + self.file
+    }
+
+    #[must_use]
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < 64 {
+            Some(Self {
+                file: index % 8,
+                rank: index / 8,
+            })
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     pub const fn rank(&self) -> usize {
         self.rank
@@ -38,6 +71,30 @@ impl Pos {
     }
 }
 
+/// Algebraic name of the position, e.g. "e4".
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", char::from(b"abcdefgh"[self.file]), self.rank + 1)
+    }
+}
+
+impl FromStr for Pos {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let (Some(file @ 'a'..='h'), Some(rank @ '1'..='8'), None) =
+            (chars.next(), chars.next(), chars.next())
+        else {
+            return Err(format!("Invalid square '{s}', must be a1-h8"));
+        };
+        Ok(Self {
+            file: file as usize - 'a' as usize,
+            rank: rank as usize - '1' as usize,
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Square {
     pos: Pos,
@@ -53,6 +110,10 @@ impl Square {
         }
     }
 
+    pub(crate) const fn empty(pos: Pos) -> Self {
+        Self { pos, content: None }
+    }
+
     #[must_use]
     pub const fn pos(&self) -> Pos {
         self.pos
@@ -0,0 +1,35 @@
+//! Zobrist keys for hashing positions.
+
+use crate::{Piece, Pos};
+
+/// Pseudo-random keys for every piece on every square, generated at compile time
+/// with splitmix64 so that keys are the same in every build.
+const PIECE_KEYS: [[[u64; 64]; 6]; 2] = {
+    let mut keys = [[[0; 64]; 6]; 2];
+    let mut state: u64 = 0x0C4E_55B0_A2D1_7F39;
+    let mut team = 0;
+    while team < 2 {
+        let mut piece_type = 0;
+        while piece_type < 6 {
+            let mut square = 0;
+            while square < 64 {
+                state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                let mut z = state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                keys[team][piece_type][square] = z ^ (z >> 31);
+                square += 1;
+            }
+            piece_type += 1;
+        }
+        team += 1;
+    }
+    keys
+};
+
+/// Key of the position having black on move.
+pub const BLACK_TO_MOVE: u64 = 0x4F1B_BCDC_BFA5_4F07;
+
+pub const fn piece_key(piece: Piece, pos: Pos) -> u64 {
+    PIECE_KEYS[piece.team().index()][piece.piece_type().index()][pos.index()]
+}
@@ -0,0 +1,76 @@
+//! Replays the games of the PGN files in `tests/games` and checks that games
+//! ending in checkmate or stalemate end with the result of their `Result` tag.
+//!
+//! The game knows neither castling, en passant nor promotion, so the fixtures
+//! are games without them.
+
+use std::fs;
+use std::path::Path;
+
+use chess::pgn::{self, Game, GameResult};
+use chess::{Outcome, Position, Team};
+
+/// Final position of `game`, every move must be legal.
+fn replay(game: &Game) -> Result<Position, String> {
+    let mut position = Position::default();
+    for (ply, san) in game.moves.iter().enumerate() {
+        let (next, _) = position
+            .play_san(san)
+            .map_err(|err| format!("move {} '{san}': {err}", ply / 2 + 1))?;
+        position = next;
+    }
+    Ok(position)
+}
+
+#[test]
+fn games_end_with_their_result() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/games");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect()
+        })
+        .unwrap_or_else(|err| panic!("{}: {err}", dir.display()));
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "pgn"));
+    paths.sort();
+
+    let (mut games, mut mates, mut stalemates) = (0, 0, 0);
+    for path in paths {
+        let pgn =
+            fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+        let parsed = pgn::parse(&pgn).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+        for game in parsed {
+            let name = format!(
+                "{} game {}",
+                path.display(),
+                game.tag("Event").unwrap_or("?")
+            );
+            let position = replay(&game).unwrap_or_else(|err| panic!("{name}: {err}"));
+            games += 1;
+            match position.outcome() {
+                Some(Outcome::Checkmate { winner }) => {
+                    let expected = match winner {
+                        Team::White => GameResult::WhiteWins,
+                        Team::Black => GameResult::BlackWins,
+                    };
+                    assert_eq!(game.result(), expected, "{name} ends in checkmate");
+                    mates += 1;
+                }
+                Some(Outcome::Stalemate) => {
+                    assert_eq!(game.result(), GameResult::Draw, "{name} ends in stalemate");
+                    stalemates += 1;
+                }
+                None => assert!(
+                    !game.moves.last().is_some_and(|san| san.ends_with('#')),
+                    "{name} ends with a mate not on the board"
+                ),
+            }
+        }
+    }
+    assert!(games > 0, "no games in {}", dir.display());
+    assert!(
+        mates > 0 && stalemates > 0,
+        "fixtures lack checkmates or stalemates"
+    );
+}
@@ -0,0 +1,143 @@
+//! Property tests playing random legal games and checking invariants of the
+//! move generator after every move.
+
+use chess::{Board, MoveList, PieceType, Pos, Position, Side, Square, Team, White};
+use proptest::prelude::*;
+
+/// Plies of a random game at most, it ends earlier when the side on move
+/// cannot move.
+const MAX_PLIES: usize = 120;
+
+fn kings<S: Side + Clone>(board: &Board<S>, team: Team) -> Vec<Pos> {
+    Pos::all()
+        .filter(|&pos| {
+            board
+                .at(pos)
+                .is_some_and(|piece| piece.team() == team && piece.piece_type() == PieceType::King)
+        })
+        .collect()
+}
+
+/// Checks the invariants of `board` and plays the legal move picked by
+/// `choice`, `None` at the end of the game.
+fn check_and_play<S: Side + Clone>(
+    board: &Board<S>,
+    choice: usize,
+) -> Result<Option<Board<S::Other>>, TestCaseError> {
+    let ours = kings(board, board.team());
+    let theirs = kings(board, board.team().enemy());
+    prop_assert_eq!(
+        ours.len(),
+        1,
+        "one king of the side on move in {}",
+        board.fen()
+    );
+    prop_assert_eq!(
+        theirs.len(),
+        1,
+        "one king of the other side in {}",
+        board.fen()
+    );
+    prop_assert!(
+        !board.is_attacked(theirs[0], board.team()),
+        "side not on move in check in {}",
+        board.fen()
+    );
+
+    let fen = board.fen();
+    let parsed = Board::<S>::from_fen(&fen).map_err(TestCaseError::fail)?;
+    prop_assert_eq!(parsed.fen(), fen.clone());
+    prop_assert_eq!(parsed.hash_key(), board.hash_key(), "hash key of {}", fen);
+    prop_assert_eq!(Position::from(board).hash_key(), board.hash_key());
+
+    let moves = board.team_legal_moves();
+    let names = |moves: &MoveList<S>| -> Vec<String> {
+        moves.iter().map(|mv| mv.data().to_string()).collect()
+    };
+    prop_assert_eq!(
+        names(&parsed.team_legal_moves()),
+        names(&moves),
+        "moves of {}",
+        fen
+    );
+    prop_assert_eq!(board.legal_moves().count(), moves.len());
+    let by_square: usize = Pos::all()
+        .map(|pos| board.legal_moves_sq(Square::new(pos, board)).len())
+        .sum();
+    prop_assert_eq!(by_square, moves.len(), "moves by square of {}", fen);
+
+    if moves.is_empty() {
+        prop_assert!(board.outcome().is_some());
+        return Ok(None);
+    }
+    Ok(Some(board.apply_move(&moves[choice % moves.len()])))
+}
+
+/// Plays the game picked by `choices` from the start position.
+fn play(choices: &[usize]) -> Result<(), TestCaseError> {
+    let mut position = Position::default();
+    for &choice in choices {
+        position = match &position {
+            Position::White(board) => match check_and_play(board, choice)? {
+                Some(board) => Position::Black(board),
+                None => return Ok(()),
+            },
+            Position::Black(board) => match check_and_play(board, choice)? {
+                Some(board) => Position::White(board),
+                None => return Ok(()),
+            },
+        };
+    }
+    Ok(())
+}
+
+/// Number of leaf nodes of the legal move tree `depth` plies deep.
+fn perft<S: Side + Clone>(board: &Board<S>, depth: u32) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    board
+        .team_legal_moves()
+        .iter()
+        .map(|mv| perft(&board.apply_move(mv), depth - 1))
+        .sum()
+}
+
+const fn distance(a: Pos, b: Pos) -> usize {
+    let ranks = a.rank().abs_diff(b.rank());
+    let files = a.file().abs_diff(b.file());
+    if ranks > files { ranks } else { files }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn random_games_keep_invariants(choices in prop::collection::vec(any::<usize>(), 0..MAX_PLIES)) {
+        play(&choices)?;
+    }
+
+    #[test]
+    fn lone_king_moves_to_every_free_neighbour(ours in 0..64usize, theirs in 0..64usize) {
+        let (ours, theirs) = (Pos::from_index(ours).unwrap(), Pos::from_index(theirs).unwrap());
+        prop_assume!(distance(ours, theirs) > 1);
+        // digits of empty squares may follow each other, they add up
+        let mut squares = ['1'; 64];
+        squares[ours.index()] = 'K';
+        squares[theirs.index()] = 'k';
+        let ranks: Vec<String> = squares.chunks(8).rev().map(|rank| rank.iter().collect()).collect();
+        let board = Board::<White>::from_fen(&ranks.join("/")).map_err(TestCaseError::fail)?;
+        let free = Pos::all()
+            .filter(|&pos| distance(pos, ours) == 1 && distance(pos, theirs) > 1)
+            .count();
+        prop_assert_eq!(board.team_legal_moves().len(), free, "moves of {}", board.fen());
+    }
+}
+
+#[test]
+fn perft_counts() {
+    assert_eq!(perft(&Board::default(), 3), 8902);
+    // no castling, en passant or promotion within two plies
+    let endgame = Board::<White>::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w");
+    assert_eq!(endgame.map(|board| perft(&board, 2)), Ok(191));
+}